@@ -0,0 +1,27 @@
+//! NIST ACVP internal projection vectors, see `tests/acvp`.
+
+use std::collections::HashMap;
+
+/// Records are `key = value` lines separated by blank lines, `#` starts a comment.
+pub fn vectors<'a>(data: &'a str, set: &str) -> Vec<HashMap<&'a str, &'a str>> {
+    let vectors = data.split("\n\n")
+        .map(|record| record.lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut kv = line.splitn(2, " = ");
+                Some((kv.next()?, kv.next()?))
+            })
+            .collect::<HashMap<_, _>>()
+        )
+        .filter(|v| v.get("parameterSet") == Some(&set))
+        .collect::<Vec<_>>();
+    assert!(!vectors.is_empty(), "no {} vectors", set);
+    vectors
+}
+
+pub fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
@@ -0,0 +1,101 @@
+//! Key-committing wrapper, the ciphertext carries a commitment to the key and nonce
+//! so it can not be opened under any other key.
+//!
+//! The key is only used through HNORX, once for the commitment key and once for the key of `AE`,
+//! each with its own domain tag. The commitment key is then chained through HNORX over
+//! the nonce, so the commitment differs per message and does not link ciphertexts.
+//! See [How to Abuse and Fix Authenticated Encryption Without Key Commitment](https://eprint.iacr.org/2020/1456.pdf).
+
+use seckey::TempKey;
+use crate::{ Concat, Error };
+use norx_permutation::U;
+use super::xnorx::{ hnorx_domain, HNORX_NONCE_LENGTH, COMMITTING_KEY_DOMAIN, COMMITMENT_DOMAIN };
+use super::AeadCipher;
+
+
+pub const COMMITMENT_LENGTH: usize = 32;
+
+/// The tag is the tag of `AE` followed by the commitment.
+pub struct Committing<AE: AeadCipher<Key = [u8; 32]>> {
+    ae: AE,
+    key: [u8; 32]
+}
+
+fn derive(key: &[u8; 32], domain: U, output: &mut [u8; 32]) {
+    hnorx_domain(key, &[0; HNORX_NONCE_LENGTH], domain, output);
+}
+
+impl<AE: AeadCipher<Key = [u8; 32]>> Committing<AE> {
+    /// The nonce is zero padded to whole HNORX nonces, its length is fixed by `AE`.
+    fn commitment(&self, nonce: &AE::Nonce) -> [u8; COMMITMENT_LENGTH] {
+        let mut commitment = self.key;
+        for chunk in nonce.as_ref().chunks(HNORX_NONCE_LENGTH) {
+            let mut block = [0; HNORX_NONCE_LENGTH];
+            block[..chunk.len()].copy_from_slice(chunk);
+            let key = commitment;
+            hnorx_domain(&key, &block, COMMITMENT_DOMAIN, &mut commitment);
+        }
+        commitment
+    }
+
+    fn check(&self, nonce: &AE::Nonce, commitment: &[u8]) -> Result<(), Error> {
+        let diff = self.commitment(nonce).iter()
+            .zip(commitment)
+            .fold(0, |sum, (x, y)| sum | (x ^ y));
+
+        if diff == 0 {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+impl<AE: AeadCipher<Key = [u8; 32]>> AeadCipher for Committing<AE> {
+    type Key = [u8; 32];
+    type Nonce = AE::Nonce;
+    type Tag = Concat<AE::Tag, [u8; COMMITMENT_LENGTH]>;
+
+    fn new(key: &Self::Key) -> Self {
+        let mut commitment_key = [0; 32];
+        derive(key, COMMITMENT_DOMAIN, &mut commitment_key);
+
+        let mut subkey = [0; 32];
+        let mut subkey = TempKey::from(&mut subkey);
+        derive(key, COMMITTING_KEY_DOMAIN, &mut subkey);
+
+        Committing { ae: AE::new(&subkey), key: commitment_key }
+    }
+
+    fn seal(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        if input.len() + Self::TAG_LENGTH != output.len() {
+            return Err(Error::Length);
+        }
+
+        let (output, commitment) = output.split_at_mut(output.len() - COMMITMENT_LENGTH);
+        self.ae.seal(nonce, aad, input, output)?;
+        commitment.copy_from_slice(&self.commitment(nonce));
+        Ok(())
+    }
+
+    fn open(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        if input.len() != output.len() + Self::TAG_LENGTH {
+            return Err(Error::Length);
+        }
+
+        let (input, commitment) = input.split_at(input.len() - COMMITMENT_LENGTH);
+        self.check(nonce, commitment)?;
+        self.ae.open(nonce, aad, input, output)
+    }
+
+    fn seal_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<Self::Tag, Error> {
+        let tag = self.ae.seal_in_place_detached(nonce, aad, buf)?;
+        Ok(Concat(tag, self.commitment(nonce)))
+    }
+
+    fn open_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8], tag: &Self::Tag) -> Result<(), Error> {
+        let Concat(tag, commitment) = tag;
+        self.check(nonce, commitment)?;
+        self.ae.open_in_place_detached(nonce, aad, buf, tag)
+    }
+}
@@ -0,0 +1,178 @@
+use std::cmp;
+use std::io::{ self, Read, Write };
+use crate::Error;
+use super::{ Online, Encryption, Decryption };
+
+
+const CHUNK_LENGTH: usize = 8 * 1024;
+
+/// Encrypts everything written to it, `finish` must be called to write the tag.
+pub struct EncryptWriter<'a, AE: Online<'a>, W: Write> {
+    process: AE::Encryption,
+    inner: W,
+    pending: usize,
+    output: Vec<u8>
+}
+
+/// Decrypts everything read from `inner`.
+///
+/// NOTE the plaintext is not authenticated until `read` returns `Ok(0)`,
+/// use `stream` if it must not be released before that.
+/// After a verification or length error every later `read` returns the same error.
+pub struct DecryptReader<'a, AE: Online<'a>, R: Read> {
+    process: Option<AE::Decryption>,
+    error: Option<Error>,
+    inner: R,
+    buf: Vec<u8>,
+    pending: usize,
+    output: Vec<u8>,
+    pos: usize
+}
+
+impl<'a, AE, W> EncryptWriter<'a, AE, W>
+    where
+        AE: Online<'a>,
+        W: Write
+{
+    pub fn new(ae: &'a AE, nonce: &AE::Nonce, aad: &[u8], inner: W) -> Self {
+        EncryptWriter {
+            process: ae.encrypt(nonce, aad),
+            inner,
+            pending: 0,
+            output: Vec::new()
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush_output()?;
+
+        let EncryptWriter { process, mut inner, pending, mut output } = self;
+        output.resize(pending + AE::TAG_LENGTH, 0);
+        process.finalize(&[], &mut output)?;
+        inner.write_all(&output)?;
+
+        Ok(inner)
+    }
+
+    fn flush_output(&mut self) -> io::Result<()> {
+        if !self.output.is_empty() {
+            self.inner.write_all(&self.output)?;
+            self.output.clear();
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, AE, W> Write for EncryptWriter<'a, AE, W>
+    where
+        AE: Online<'a>,
+        W: Write
+{
+    fn write(&mut self, input: &[u8]) -> io::Result<usize> {
+        self.flush_output()?;
+
+        self.output.resize(self.pending + input.len(), 0);
+        let take = self.process.process(input, &mut self.output)?;
+        self.output.truncate(take);
+        self.pending = self.pending + input.len() - take;
+
+        Ok(input.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_output()?;
+        self.inner.flush()
+    }
+}
+
+impl<'a, AE, R> DecryptReader<'a, AE, R>
+    where
+        AE: Online<'a>,
+        R: Read
+{
+    pub fn new(ae: &'a AE, nonce: &AE::Nonce, aad: &[u8], inner: R) -> Self {
+        DecryptReader {
+            process: Some(ae.decrypt(nonce, aad)),
+            error: None,
+            inner,
+            buf: Vec::new(),
+            pending: 0,
+            output: Vec::new(),
+            pos: 0
+        }
+    }
+
+    fn fill_output(&mut self) -> io::Result<()> {
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK_LENGTH, 0);
+        let n = match self.inner.read(&mut self.buf[len..]) {
+            Ok(n) => n,
+            Err(err) => {
+                self.buf.truncate(len);
+                return Err(err);
+            }
+        };
+        self.buf.truncate(len + n);
+        self.pos = 0;
+
+        if let Err(err) = self.decrypt(n == 0) {
+            self.output.clear();
+            self.error = Some(err);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    fn decrypt(&mut self, eof: bool) -> Result<(), Error> {
+        if eof {
+            let process = match self.process.take() {
+                Some(process) => process,
+                None => return Ok(())
+            };
+            let len = (self.pending + self.buf.len())
+                .checked_sub(AE::TAG_LENGTH)
+                .ok_or(Error::Length)?;
+            self.output.resize(len, 0);
+            process.finalize(&self.buf, &mut self.output)?;
+            self.buf.clear();
+        } else if let Some(process) = self.process.as_mut() {
+            // keep the last `TAG_LENGTH` bytes, they may be the tag
+            let len = self.buf.len().saturating_sub(AE::TAG_LENGTH);
+            self.output.resize(self.pending + len, 0);
+            let take = process.process(&self.buf[..len], &mut self.output)?;
+            self.output.truncate(take);
+            self.pending = self.pending + len - take;
+            self.buf.drain(..len);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, AE, R> Read for DecryptReader<'a, AE, R>
+    where
+        AE: Online<'a>,
+        R: Read
+{
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() {
+            if let Some(err) = self.error {
+                return Err(err.into());
+            }
+
+            if self.process.is_none() {
+                return Ok(0);
+            }
+
+            self.fill_output()?;
+        }
+
+        let n = cmp::min(output.len(), self.output.len() - self.pos);
+        output[..n].copy_from_slice(&self.output[self.pos..][..n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
@@ -1,19 +1,68 @@
-use crate::Error;
+use crate::{ ByteArray, Error };
 
+#[macro_use] mod norx_core;
+mod mrs_core;
+
+pub mod norx3241;
 pub mod norx6441;
+pub mod norx6444;
 pub mod norx_mrs;
+pub mod xnorx;
+pub mod committing;
+pub mod rekey;
+pub mod nonce;
+pub mod stream;
+pub mod io;
 
 
 pub trait AeadCipher {
-    const KEY_LENGTH: usize;
-    const NONCE_LENGTH: usize;
-    const TAG_LENGTH: usize;
-
-    /// TODO should be `Self::KEY_LENGTH`
-    fn new(key: &[u8]) -> Self;
-    /// TODO should be `Self::NONCE_LENGTH`
-    fn seal(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error>;
-    fn open(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error>;
+    type Key: ByteArray;
+    type Nonce: ByteArray;
+    type Tag: ByteArray;
+
+    const KEY_LENGTH: usize = <Self::Key as ByteArray>::LENGTH;
+    const NONCE_LENGTH: usize = <Self::Nonce as ByteArray>::LENGTH;
+    const TAG_LENGTH: usize = <Self::Tag as ByteArray>::LENGTH;
+
+    fn new(key: &Self::Key) -> Self;
+    fn seal(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error>;
+    fn open(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error>;
+
+    fn seal_to_vec(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut output = vec![0; input.len() + Self::TAG_LENGTH];
+        self.seal(nonce, aad, input, &mut output)?;
+        Ok(output)
+    }
+
+    fn open_to_vec(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        let len = input.len().checked_sub(Self::TAG_LENGTH).ok_or(Error::Length)?;
+        let mut output = vec![0; len];
+        self.open(nonce, aad, input, &mut output)?;
+        Ok(output)
+    }
+
+    fn seal_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<Self::Tag, Error>;
+
+    /// If the tag does not match, `buf` is zeroed.
+    fn open_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8], tag: &Self::Tag) -> Result<(), Error>;
+
+    /// `buf` is the plaintext followed by `TAG_LENGTH` bytes for the tag.
+    fn seal_in_place(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<(), Error> {
+        let len = buf.len().checked_sub(Self::TAG_LENGTH).ok_or(Error::Length)?;
+        let (buf, tag) = buf.split_at_mut(len);
+        let t = self.seal_in_place_detached(nonce, aad, buf)?;
+        tag.copy_from_slice(t.as_ref());
+        Ok(())
+    }
+
+    /// `buf` is the ciphertext followed by the tag, the plaintext is left in `buf[..buf.len() - TAG_LENGTH]`.
+    fn open_in_place(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<(), Error> {
+        let len = buf.len().checked_sub(Self::TAG_LENGTH).ok_or(Error::Length)?;
+        let (buf, tag) = buf.split_at_mut(len);
+        let mut t = Self::Tag::zero();
+        t.as_mut().copy_from_slice(tag);
+        self.open_in_place_detached(nonce, aad, buf, &t)
+    }
 }
 
 
@@ -22,18 +71,24 @@ pub trait Online<'a>: AeadCipher {
     type Encryption: Encryption<'a>;
     type Decryption: Decryption<'a>;
 
-    /// TODO should be `Self::NONCE_LENGTH`
-    fn encrypt(&'a self, nonce: &[u8], aad: &[u8]) -> Self::Encryption;
-    /// TODO should be `Self::NONCE_LENGTH`
-    fn decrypt(&'a self, nonce: &[u8], aad: &[u8]) -> Self::Decryption;
+    fn encrypt(&'a self, nonce: &Self::Nonce, aad: &[u8]) -> Self::Encryption;
+    fn decrypt(&'a self, nonce: &Self::Nonce, aad: &[u8]) -> Self::Decryption;
 }
 
 pub trait Encryption<'a> {
-    fn process<'b>(&mut self, input: &[u8], output: &'b mut [u8]) -> &'b [u8];
+    /// Partial blocks are buffered, returns the number of bytes written to `output`.
+    fn process(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, Error>;
+
+    /// `output` must hold the buffered bytes, `input` and the tag.
     fn finalize(self, input: &[u8], output: &mut [u8]) -> Result<(), Error>;
 }
 
 pub trait Decryption<'a> {
-    fn process<'b>(&mut self, input: &[u8], output: &'b mut [u8]) -> &'b [u8];
+    /// Partial blocks are buffered, returns the number of bytes written to `output`.
+    ///
+    /// NOTE the tag must not be passed here.
+    fn process(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, Error>;
+
+    /// `input` must end with the tag, `output` must hold the buffered bytes and the rest of `input`.
     fn finalize(self, input: &[u8], output: &mut [u8]) -> Result<(), Error>;
 }
@@ -0,0 +1,123 @@
+//! MRS mode of operation over the NORX64-4 permutation, as in `mem_aead_mrs`,
+//! with the absorption pass and the encryption pass as separate states
+//! so `NorxMRS` can run them over a stream.
+
+use norx_permutation::{ U, S, norx };
+use arrayref::array_ref;
+use mem_aead_mrs::{ KEY_LENGTH, NONCE_LENGTH, TAG_LENGTH, STATE_LENGTH, BLOCK_LENGTH };
+
+
+const L: U = 4;
+const T: U = 4 * 64;
+
+const ABS_TAG: U = 0x00;
+const ENC_TAG: U = 0x01;
+
+pub struct State([U; S]);
+
+impl State {
+    fn new(key: &[u8; KEY_LENGTH], nonce: &[u8], tag: U) -> Self {
+        let mut state = [0; S];
+
+        for (i, word) in nonce.chunks(8).enumerate() {
+            state[i] = load(word);
+        }
+        state[9] = L;
+        state[10] = T;
+        state[11] = tag;
+        for i in 0..4 {
+            state[i + 12] = load(&key[i * 8..]);
+        }
+
+        State(state)
+    }
+
+    pub fn absorption(key: &[u8; KEY_LENGTH], nonce: &[u8; NONCE_LENGTH]) -> Self {
+        State::new(key, nonce, ABS_TAG)
+    }
+
+    pub fn encryption(key: &[u8; KEY_LENGTH], tag: &[u8; TAG_LENGTH]) -> Self {
+        State::new(key, tag, ENC_TAG)
+    }
+
+    #[inline]
+    fn bytes(&self) -> [u8; STATE_LENGTH] {
+        let mut bytes = [0; STATE_LENGTH];
+        for i in 0..S {
+            bytes[i * 8..][..8].copy_from_slice(&self.0[i].to_le_bytes());
+        }
+        bytes
+    }
+
+    #[inline]
+    fn set_bytes(&mut self, bytes: &[u8; STATE_LENGTH]) {
+        for i in 0..S {
+            self.0[i] = load(&bytes[i * 8..]);
+        }
+    }
+
+    /// `data` is whole `STATE_LENGTH` blocks, except at the end of the aad or the message.
+    pub fn absorb(&mut self, data: &[u8]) {
+        for block in data.chunks(STATE_LENGTH) {
+            norx(&mut self.0);
+
+            let mut state = self.bytes();
+            for (s, b) in state.iter_mut().zip(block) {
+                *s ^= b;
+            }
+            self.set_bytes(&state);
+        }
+    }
+
+    pub fn finalize(mut self, aad_length: u64, length: u64) -> [u8; TAG_LENGTH] {
+        norx(&mut self.0);
+        self.0[0] ^= aad_length;
+        self.0[1] ^= length;
+        norx(&mut self.0);
+
+        let mut tag = [0; TAG_LENGTH];
+        tag.copy_from_slice(&self.bytes()[..TAG_LENGTH]);
+        tag
+    }
+
+    /// `buf` is whole `BLOCK_LENGTH` blocks, except at the end of the message.
+    pub fn encrypt(&mut self, buf: &mut [u8]) {
+        for block in buf.chunks_mut(BLOCK_LENGTH) {
+            norx(&mut self.0);
+
+            let mut state = self.bytes();
+            for (s, b) in state.iter_mut().zip(block.iter_mut()) {
+                *b ^= *s;
+                *s = *b;
+            }
+
+            // the last partial block is not fed back
+            if block.len() == BLOCK_LENGTH {
+                self.set_bytes(&state);
+            }
+        }
+    }
+
+    /// `buf` is whole `BLOCK_LENGTH` blocks, except at the end of the message.
+    pub fn decrypt(&mut self, buf: &mut [u8]) {
+        for block in buf.chunks_mut(BLOCK_LENGTH) {
+            norx(&mut self.0);
+
+            let mut state = self.bytes();
+            for (s, b) in state.iter_mut().zip(block.iter_mut()) {
+                let c = *b;
+                *b ^= *s;
+                *s = c;
+            }
+
+            if block.len() == BLOCK_LENGTH {
+                self.set_bytes(&state);
+            }
+        }
+    }
+}
+
+#[inline]
+fn load(buf: &[u8]) -> U {
+    U::from_le_bytes(*array_ref!(buf, 0, 8))
+}
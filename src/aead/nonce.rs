@@ -0,0 +1,54 @@
+use std::cmp;
+use std::marker::PhantomData;
+use crate::{ ByteArray, Error };
+use super::AeadCipher;
+
+
+/// Generates `prefix || counter` nonces, the counter is big-endian
+/// and takes the remaining bytes of the nonce, up to 8.
+///
+/// Fails with `Error::NonceExhausted` instead of wrapping around.
+pub struct NonceSequence<AE: AeadCipher> {
+    nonce: AE::Nonce,
+    width: usize,
+    counter: Option<u64>,
+    _ae: PhantomData<AE>
+}
+
+impl<AE: AeadCipher> NonceSequence<AE> {
+    pub fn new() -> Self {
+        NonceSequence::with_prefix(&[]).unwrap()
+    }
+
+    /// `prefix` must leave at least one byte of `AE::NONCE_LENGTH` for the counter.
+    pub fn with_prefix(prefix: &[u8]) -> Result<Self, Error> {
+        if prefix.len() >= AE::NONCE_LENGTH {
+            return Err(Error::Length);
+        }
+
+        let width = cmp::min(AE::NONCE_LENGTH - prefix.len(), 8);
+        let mut nonce = AE::Nonce::zero();
+        nonce.as_mut()[..prefix.len()].copy_from_slice(prefix);
+
+        Ok(NonceSequence { nonce, width, counter: Some(0), _ae: PhantomData })
+    }
+
+    /// Returns the next nonce, each value is returned only once.
+    pub fn advance(&mut self) -> Result<&AE::Nonce, Error> {
+        let counter = self.counter.ok_or(Error::NonceExhausted)?;
+        let max = u64::MAX >> (64 - self.width * 8);
+        self.counter = if counter < max { Some(counter + 1) } else { None };
+
+        let nonce = self.nonce.as_mut();
+        let len = nonce.len();
+        nonce[len - self.width..].copy_from_slice(&counter.to_be_bytes()[8 - self.width..]);
+
+        Ok(&self.nonce)
+    }
+}
+
+impl<AE: AeadCipher> Default for NonceSequence<AE> {
+    fn default() -> Self {
+        NonceSequence::new()
+    }
+}
@@ -0,0 +1,4 @@
+norx!(
+    /// NORX32-4-1, for targets where 64-bit words are slow.
+    Norx3241, u32, 1
+);
@@ -1,8 +1,9 @@
-use std::cmp;
+use std::iter;
 use arrayref::{ array_ref, array_mut_ref };
 use norx::constant::{ KEY_LENGTH, NONCE_LENGTH, TAG_LENGTH, BLOCK_LENGTH };
 use norx::{ Norx as NorxCipher, Process, Encrypt, Decrypt };
 use crate::Error;
+use super::norx_core::buffered;
 use super::{ AeadCipher, Online, Encryption, Decryption };
 
 
@@ -10,120 +11,159 @@ pub struct Norx6441([u8; KEY_LENGTH]);
 
 pub struct EncryptProcess<'a> {
     process: Process<Encrypt>,
-    key: &'a [u8; KEY_LENGTH]
+    key: &'a [u8; KEY_LENGTH],
+    buf: [u8; BLOCK_LENGTH],
+    pos: usize
 }
 
 pub struct DecryptProcess<'a> {
     process: Process<Decrypt>,
-    key: &'a [u8; KEY_LENGTH]
+    key: &'a [u8; KEY_LENGTH],
+    buf: [u8; BLOCK_LENGTH],
+    pos: usize
 }
 
 impl AeadCipher for Norx6441 {
-    const KEY_LENGTH: usize = KEY_LENGTH;
-    const NONCE_LENGTH: usize = NONCE_LENGTH;
-    const TAG_LENGTH: usize = TAG_LENGTH;
-
-    fn new(key: &[u8]) -> Self {
-        let mut k = [0; KEY_LENGTH];
-        k.copy_from_slice(key);
-        Norx6441(k)
+    type Key = [u8; KEY_LENGTH];
+    type Nonce = [u8; NONCE_LENGTH];
+    type Tag = [u8; TAG_LENGTH];
+
+    fn new(key: &Self::Key) -> Self {
+        Norx6441(*key)
     }
 
-    fn seal(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+    fn seal(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
         self.encrypt(nonce, aad).finalize(input, output)
     }
 
-    fn open(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+    fn open(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
         self.decrypt(nonce, aad).finalize(input, output)
     }
+
+    fn seal_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<Self::Tag, Error> {
+        let mut process = NorxCipher::new(&self.0, nonce).encrypt(aad);
+
+        // `norx` does not work in place, so only one block is copied at a time
+        let len = buf.len() - buf.len() % BLOCK_LENGTH;
+        let (buf, remaining) = buf.split_at_mut(len);
+        let mut block = [0; BLOCK_LENGTH];
+        for chunk in buf.chunks_mut(BLOCK_LENGTH) {
+            block.copy_from_slice(chunk);
+            process.process(iter::once((&block, array_mut_ref!(chunk, 0, BLOCK_LENGTH))));
+        }
+
+        let mut output = [0; BLOCK_LENGTH + TAG_LENGTH];
+        let output = &mut output[..remaining.len() + TAG_LENGTH];
+        process.finalize(&self.0, &[], remaining, output);
+        remaining.copy_from_slice(&output[..remaining.len()]);
+
+        Ok(*array_ref!(output, remaining.len(), TAG_LENGTH))
+    }
+
+    fn open_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8], tag: &Self::Tag) -> Result<(), Error> {
+        let mut process = NorxCipher::new(&self.0, nonce).decrypt(aad);
+
+        let len = buf.len() - buf.len() % BLOCK_LENGTH;
+        let (blocks, remaining) = buf.split_at_mut(len);
+        let mut block = [0; BLOCK_LENGTH];
+        for chunk in blocks.chunks_mut(BLOCK_LENGTH) {
+            block.copy_from_slice(chunk);
+            process.process(iter::once((&block, array_mut_ref!(chunk, 0, BLOCK_LENGTH))));
+        }
+
+        let mut input = [0; BLOCK_LENGTH + TAG_LENGTH];
+        let input = &mut input[..remaining.len() + TAG_LENGTH];
+        input[..remaining.len()].copy_from_slice(remaining);
+        input[remaining.len()..].copy_from_slice(tag);
+
+        if process.finalize(&self.0, &[], input, remaining) {
+            Ok(())
+        } else {
+            // don't release unauthenticated plaintext
+            buf.iter_mut().for_each(|b| *b = 0);
+            Err(Error::VerificationFailed)
+        }
+    }
 }
 
 impl<'a> Online<'a> for Norx6441 {
     type Encryption = EncryptProcess<'a>;
     type Decryption = DecryptProcess<'a>;
 
-    fn encrypt(&'a self, nonce: &[u8], aad: &[u8]) -> Self::Encryption {
-        let nonce = array_ref!(nonce, 0, NONCE_LENGTH);
+    fn encrypt(&'a self, nonce: &Self::Nonce, aad: &[u8]) -> Self::Encryption {
         EncryptProcess {
             process: NorxCipher::new(&self.0, nonce).encrypt(aad),
-            key: &self.0
+            key: &self.0,
+            buf: [0; BLOCK_LENGTH],
+            pos: 0
         }
     }
 
-    fn decrypt(&'a self, nonce: &[u8], aad: &[u8]) -> Self::Decryption {
-        let nonce = array_ref!(nonce, 0, NONCE_LENGTH);
+    fn decrypt(&'a self, nonce: &Self::Nonce, aad: &[u8]) -> Self::Decryption {
         DecryptProcess {
             process: NorxCipher::new(&self.0, nonce).decrypt(aad),
-            key: &self.0
+            key: &self.0,
+            buf: [0; BLOCK_LENGTH],
+            pos: 0
         }
     }
 }
 
 impl<'a> Encryption<'a> for EncryptProcess<'a> {
-    fn process<'b>(&mut self, input: &[u8], output: &'b mut [u8]) -> &'b [u8] {
-        let len = cmp::min(input.len(), output.len());
-
-        let (input, _) = input.split_at(len - len % BLOCK_LENGTH);
-        let (output, _) = output.split_at_mut(input.len());
-
-        self.process.process(
-            input.chunks(BLOCK_LENGTH)
-                .zip(output.chunks_mut(BLOCK_LENGTH))
-                .map(|(input, output)| (
-                    array_ref!(input, 0, BLOCK_LENGTH),
-                    array_mut_ref!(output, 0, BLOCK_LENGTH)
-                ))
-        );
-
-        output
+    fn process(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+        let EncryptProcess { process, buf, pos, .. } = self;
+        buffered(buf, pos, input, output, |input, output| process.process(blocks(input, output)))
     }
 
     fn finalize(mut self, input: &[u8], output: &mut [u8]) -> Result<(), Error> {
-        if input.len() + TAG_LENGTH != output.len() {
+        if self.pos + input.len() + TAG_LENGTH != output.len() {
             return Err(Error::Length);
         }
 
-        let take = self.process(input, output).len();
-        let (_, input) = input.split_at(take);
+        let take = self.process(input, output)?;
         let (_, output) = output.split_at_mut(take);
-        self.process.finalize(self.key, &[], input, output);
+        self.process.finalize(self.key, &[], &self.buf[..self.pos], output);
 
         Ok(())
     }
 }
 
 impl<'a> Decryption<'a> for DecryptProcess<'a> {
-    fn process<'b>(&mut self, input: &[u8], output: &'b mut [u8]) -> &'b [u8] {
-        let len = cmp::min(input.len(), output.len());
-
-        let (input, _) = input.split_at(len - len % BLOCK_LENGTH);
-        let (output, _) = output.split_at_mut(input.len());
-
-        self.process.process(
-            input.chunks(BLOCK_LENGTH)
-                .zip(output.chunks_mut(BLOCK_LENGTH))
-                .map(|(input, output)| (
-                    array_ref!(input, 0, BLOCK_LENGTH),
-                    array_mut_ref!(output, 0, BLOCK_LENGTH)
-                ))
-        );
-
-        output
+    fn process(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+        let DecryptProcess { process, buf, pos, .. } = self;
+        buffered(buf, pos, input, output, |input, output| process.process(blocks(input, output)))
     }
 
     fn finalize(mut self, input: &[u8], output: &mut [u8]) -> Result<(), Error> {
-        if input.len() != output.len() + TAG_LENGTH {
+        if input.len() < TAG_LENGTH || self.pos + input.len() != output.len() + TAG_LENGTH {
             return Err(Error::Length);
         }
 
-        let take = self.process(input, output).len();
-        let (_, input) = input.split_at(take);
+        let (input, tag) = input.split_at(input.len() - TAG_LENGTH);
+        let take = self.process(input, output)?;
         let (_, output) = output.split_at_mut(take);
 
-        if self.process.finalize(self.key, &[], input, output) {
+        let mut remaining = [0; BLOCK_LENGTH + TAG_LENGTH];
+        let remaining = &mut remaining[..self.pos + TAG_LENGTH];
+        remaining[..self.pos].copy_from_slice(&self.buf[..self.pos]);
+        remaining[self.pos..].copy_from_slice(tag);
+
+        if self.process.finalize(self.key, &[], remaining, output) {
             Ok(())
         } else {
             Err(Error::VerificationFailed)
         }
     }
 }
+
+
+fn blocks<'a>(input: &'a [u8], output: &'a mut [u8])
+    -> impl Iterator<Item = (&'a [u8; BLOCK_LENGTH], &'a mut [u8; BLOCK_LENGTH])>
+{
+    input.chunks(BLOCK_LENGTH)
+        .zip(output.chunks_mut(BLOCK_LENGTH))
+        .map(|(input, output)| (
+            array_ref!(input, 0, BLOCK_LENGTH),
+            array_mut_ref!(output, 0, BLOCK_LENGTH)
+        ))
+}
@@ -0,0 +1,6 @@
+use norx_permutation::U;
+
+norx!(
+    /// NORX64-4-4, the lane-parallel variant of `Norx6441`.
+    Norx6444, U, 4
+);
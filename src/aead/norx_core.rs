@@ -0,0 +1,655 @@
+//! NORX v3.0 mode of operation over the `norx_permutation` state,
+//! for the variants that the `norx` crate cannot provide alongside `Norx6441`.
+//!
+//! `norx_permutation` is built for 64-bit words, the 32-bit permutation lives here.
+
+use std::cmp;
+use std::ops::BitXorAssign;
+use norx_permutation::{ U, S, norx, norx_x4 };
+use crate::Error;
+
+
+const L: u32 = 4;
+const R: usize = 12;
+const MAX_BLOCK_LENGTH: usize = R * 8;
+const MAX_TAG_LENGTH: usize = 4 * 8;
+
+const HEADER_TAG: u32 = 0x01;
+const PAYLOAD_TAG: u32 = 0x02;
+const FINAL_TAG: u32 = 0x08;
+const BRANCH_TAG: u32 = 0x10;
+const MERGE_TAG: u32 = 0x20;
+
+pub trait Word: Copy + Default + BitXorAssign + From<u32> {
+    const BYTES: usize;
+
+    /// `u8 .. u15`, the last eight words of `F^2(0, 1, .., 15)`.
+    const INIT: [Self; 8];
+
+    fn load(buf: &[u8]) -> Self;
+    fn store(self, buf: &mut [u8]);
+
+    /// `F^L`
+    fn permute(state: &mut [Self; S]);
+
+    /// `F^L` on each of the lanes, computed together.
+    fn permute_lanes(lanes: &mut [&mut [Self; S]]);
+}
+
+impl Word for U {
+    const BYTES: usize = 8;
+    const INIT: [U; 8] = [
+        0xB15E641748DE5E6B, 0xAA95E955E10F8410, 0x28D1034441A9DD40, 0x7F31BBF964E93BF5,
+        0xB5E9E22493DFFB96, 0xB980C852479FAFBD, 0xDA24516BF55EAFD4, 0x86026AE8536F1501
+    ];
+
+    #[inline]
+    fn load(buf: &[u8]) -> Self {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&buf[..8]);
+        U::from_le_bytes(bytes)
+    }
+
+    #[inline]
+    fn store(self, buf: &mut [u8]) {
+        buf[..8].copy_from_slice(&self.to_le_bytes());
+    }
+
+    #[inline]
+    fn permute(state: &mut [U; S]) {
+        norx(state)
+    }
+
+    fn permute_lanes(lanes: &mut [&mut [U; S]]) {
+        for lanes in lanes.chunks_mut(4) {
+            match lanes {
+                [s0, s1, s2, s3] => norx_x4(s0, s1, s2, s3),
+                _ => for state in lanes.iter_mut() {
+                    norx(state)
+                }
+            }
+        }
+    }
+}
+
+impl Word for u32 {
+    const BYTES: usize = 4;
+    const INIT: [u32; 8] = [
+        0xA3D8D930, 0x3FA8B72C, 0xED84EB49, 0xEDCA4787,
+        0x335463EB, 0xF994220B, 0xBE0BF5C9, 0xD7C49104
+    ];
+
+    #[inline]
+    fn load(buf: &[u8]) -> Self {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&buf[..4]);
+        u32::from_le_bytes(bytes)
+    }
+
+    #[inline]
+    fn store(self, buf: &mut [u8]) {
+        buf[..4].copy_from_slice(&self.to_le_bytes());
+    }
+
+    #[inline]
+    fn permute(state: &mut [u32; S]) {
+        Self::permute_lanes(&mut [state])
+    }
+
+    /// Each `G` is applied to every lane before the next.
+    fn permute_lanes(lanes: &mut [&mut [u32; S]]) {
+        #[inline]
+        fn h(x: u32, y: u32) -> u32 {
+            (x ^ y) ^ ((x & y) << 1)
+        }
+
+        #[inline]
+        fn g(lanes: &mut [&mut [u32; S]], a: usize, b: usize, c: usize, d: usize) {
+            for s in lanes.iter_mut() {
+                s[a] = h(s[a], s[b]); s[d] = (s[a] ^ s[d]).rotate_right(8);
+                s[c] = h(s[c], s[d]); s[b] = (s[b] ^ s[c]).rotate_right(11);
+                s[a] = h(s[a], s[b]); s[d] = (s[a] ^ s[d]).rotate_right(16);
+                s[c] = h(s[c], s[d]); s[b] = (s[b] ^ s[c]).rotate_right(31);
+            }
+        }
+
+        for _ in 0..L {
+            g(lanes, 0, 4,  8, 12);
+            g(lanes, 1, 5,  9, 13);
+            g(lanes, 2, 6, 10, 14);
+            g(lanes, 3, 7, 11, 15);
+
+            g(lanes, 0, 5, 10, 15);
+            g(lanes, 1, 6, 11, 12);
+            g(lanes, 2, 7,  8, 13);
+            g(lanes, 3, 4,  9, 14);
+        }
+    }
+}
+
+pub const fn block_length<W: Word>() -> usize {
+    R * W::BYTES
+}
+
+pub const fn key_length<W: Word>() -> usize {
+    4 * W::BYTES
+}
+
+
+#[derive(Clone, Copy)]
+struct State<W: Word>([W; S]);
+
+impl<W: Word> State<W> {
+    fn new(key: &[u8], nonce: &[u8], p: u32) -> Self {
+        let mut state = [W::default(); S];
+
+        for i in 0..4 {
+            state[i] = W::load(&nonce[i * W::BYTES..]);
+            state[i + 4] = W::load(&key[i * W::BYTES..]);
+        }
+        state[8..].copy_from_slice(&W::INIT);
+
+        let w = W::BYTES as u32 * 8;
+        state[12] ^= W::from(w);
+        state[13] ^= W::from(L);
+        state[14] ^= W::from(p);
+        state[15] ^= W::from(4 * w);
+
+        W::permute(&mut state);
+
+        for i in 0..4 {
+            state[i + 12] ^= W::load(&key[i * W::BYTES..]);
+        }
+
+        State(state)
+    }
+
+    #[inline]
+    fn inject(&mut self, tag: u32) {
+        self.0[15] ^= W::from(tag);
+        W::permute(&mut self.0);
+    }
+
+    fn absorb(&mut self, data: &[u8], tag: u32) {
+        if data.is_empty() {
+            return;
+        }
+
+        let block_length = block_length::<W>();
+        let mut chunks = data.chunks_exact(block_length);
+        for block in &mut chunks {
+            self.absorb_block(block, tag);
+        }
+
+        let mut block = [0; MAX_BLOCK_LENGTH];
+        let block = pad(&mut block[..block_length], chunks.remainder());
+        self.absorb_block(block, tag);
+    }
+
+    fn absorb_block(&mut self, block: &[u8], tag: u32) {
+        self.inject(tag);
+
+        for i in 0..R {
+            self.0[i] ^= W::load(&block[i * W::BYTES..]);
+        }
+    }
+
+    fn encrypt_block(&mut self, input: &[u8], output: &mut [u8]) {
+        self.inject(PAYLOAD_TAG);
+        self.encrypt_rate(input, output);
+    }
+
+    fn decrypt_block(&mut self, input: &[u8], output: &mut [u8]) {
+        self.inject(PAYLOAD_TAG);
+        self.decrypt_rate(input, output);
+    }
+
+    /// One block for each lane, the lanes are permuted together.
+    fn encrypt_lanes<const P: usize>(lanes: &mut [State<W>; P], input: &[u8], output: &mut [u8]) {
+        let block_length = block_length::<W>();
+        State::inject_lanes(lanes, PAYLOAD_TAG);
+
+        for ((lane, input), output) in lanes.iter_mut()
+            .zip(input.chunks(block_length))
+            .zip(output.chunks_mut(block_length))
+        {
+            lane.encrypt_rate(input, output);
+        }
+    }
+
+    /// One block for each lane, the lanes are permuted together.
+    fn decrypt_lanes<const P: usize>(lanes: &mut [State<W>; P], input: &[u8], output: &mut [u8]) {
+        let block_length = block_length::<W>();
+        State::inject_lanes(lanes, PAYLOAD_TAG);
+
+        for ((lane, input), output) in lanes.iter_mut()
+            .zip(input.chunks(block_length))
+            .zip(output.chunks_mut(block_length))
+        {
+            lane.decrypt_rate(input, output);
+        }
+    }
+
+    fn inject_lanes<const P: usize>(lanes: &mut [State<W>; P], tag: u32) {
+        for lane in lanes.iter_mut() {
+            lane.0[15] ^= W::from(tag);
+        }
+        W::permute_lanes(&mut lanes.each_mut().map(|lane| &mut lane.0));
+    }
+
+    #[inline]
+    fn encrypt_rate(&mut self, input: &[u8], output: &mut [u8]) {
+        for i in 0..R {
+            self.0[i] ^= W::load(&input[i * W::BYTES..]);
+            self.0[i].store(&mut output[i * W::BYTES..]);
+        }
+    }
+
+    #[inline]
+    fn decrypt_rate(&mut self, input: &[u8], output: &mut [u8]) {
+        for i in 0..R {
+            let c = W::load(&input[i * W::BYTES..]);
+            let mut m = self.0[i];
+            m ^= c;
+            m.store(&mut output[i * W::BYTES..]);
+            self.0[i] = c;
+        }
+    }
+
+    fn encrypt_lastblock(&mut self, input: &[u8], output: &mut [u8]) {
+        let mut block = [0; MAX_BLOCK_LENGTH];
+        let block = pad(&mut block[..block_length::<W>()], input);
+        let mut last = [0; MAX_BLOCK_LENGTH];
+        let last = &mut last[..block.len()];
+        self.encrypt_block(block, last);
+        output.copy_from_slice(&last[..input.len()]);
+    }
+
+    fn decrypt_lastblock(&mut self, input: &[u8], output: &mut [u8]) {
+        let block_length = block_length::<W>();
+        self.inject(PAYLOAD_TAG);
+
+        let mut block = [0; MAX_BLOCK_LENGTH];
+        let block = &mut block[..block_length];
+        for i in 0..R {
+            self.0[i].store(&mut block[i * W::BYTES..]);
+        }
+        block[..input.len()].copy_from_slice(input);
+        block[input.len()] ^= 0x01;
+        block[block_length - 1] ^= 0x80;
+
+        for i in 0..R {
+            let c = W::load(&block[i * W::BYTES..]);
+            let mut m = self.0[i];
+            m ^= c;
+            m.store(&mut block[i * W::BYTES..]);
+            self.0[i] = c;
+        }
+
+        output.copy_from_slice(&block[..input.len()]);
+    }
+
+    fn branch<const P: usize>(&self) -> [State<W>; P] {
+        let mut lanes = [*self; P];
+        State::inject_lanes(&mut lanes, BRANCH_TAG);
+
+        for (lane, state) in lanes.iter_mut().enumerate() {
+            for i in 0..R {
+                state.0[i] ^= W::from(lane as u32);
+            }
+        }
+
+        lanes
+    }
+
+    fn merge<const P: usize>(lanes: &mut [State<W>; P]) -> Self {
+        State::inject_lanes(lanes, MERGE_TAG);
+
+        let mut state = State([W::default(); S]);
+        for lane in lanes.iter() {
+            for i in 0..S {
+                state.0[i] ^= lane.0[i];
+            }
+        }
+
+        state
+    }
+
+    fn finalize(mut self, key: &[u8], tag: &mut [u8]) {
+        self.inject(FINAL_TAG);
+
+        for i in 0..4 {
+            self.0[i + 12] ^= W::load(&key[i * W::BYTES..]);
+        }
+        W::permute(&mut self.0);
+        for i in 0..4 {
+            self.0[i + 12] ^= W::load(&key[i * W::BYTES..]);
+            self.0[i + 12].store(&mut tag[i * W::BYTES..]);
+        }
+    }
+}
+
+fn pad<'a>(block: &'a mut [u8], input: &[u8]) -> &'a [u8] {
+    let len = block.len();
+    block[..input.len()].copy_from_slice(input);
+    block[input.len()] = 0x01;
+    block[len - 1] |= 0x80;
+    block
+}
+
+
+/// NORX with `P` lanes, `P = 1` is the sequential mode.
+pub struct Process<W: Word, const P: usize> {
+    state: State<W>,
+    lanes: [State<W>; P],
+    blocks: usize
+}
+
+impl<W: Word, const P: usize> Process<W, P> {
+    pub fn new(key: &[u8], nonce: &[u8], aad: &[u8]) -> Self {
+        let mut state = State::new(key, nonce, P as u32);
+        state.absorb(aad, HEADER_TAG);
+
+        Process {
+            state,
+            lanes: [state; P],
+            blocks: 0
+        }
+    }
+
+    fn lane(&mut self) -> &mut State<W> {
+        if P == 1 {
+            return &mut self.state;
+        }
+
+        if self.blocks == 0 {
+            self.lanes = self.state.branch();
+        }
+
+        &mut self.lanes[self.blocks % P]
+    }
+
+    /// Block `j` goes to lane `j % P`, each run of `P` blocks
+    /// that starts at lane 0 is processed on all lanes together.
+    fn blocks<F, G>(&mut self, input: &[u8], output: &mut [u8], block: F, lanes: G)
+        where
+            F: Fn(&mut State<W>, &[u8], &mut [u8]),
+            G: Fn(&mut [State<W>; P], &[u8], &mut [u8])
+    {
+        let block_length = block_length::<W>();
+        let mut pos = 0;
+
+        while pos < input.len() {
+            let n = P * block_length;
+
+            if P > 1 && self.blocks % P == 0 && input.len() - pos >= n {
+                if self.blocks == 0 {
+                    self.lanes = self.state.branch();
+                }
+
+                lanes(&mut self.lanes, &input[pos..][..n], &mut output[pos..][..n]);
+                self.blocks += P;
+                pos += n;
+            } else {
+                block(self.lane(), &input[pos..][..block_length], &mut output[pos..][..block_length]);
+                self.blocks += 1;
+                pos += block_length;
+            }
+        }
+    }
+
+    /// `input` and `output` are whole blocks.
+    pub fn encrypt(&mut self, input: &[u8], output: &mut [u8]) {
+        self.blocks(input, output, State::encrypt_block, State::encrypt_lanes);
+    }
+
+    /// `input` and `output` are whole blocks.
+    pub fn decrypt(&mut self, input: &[u8], output: &mut [u8]) {
+        self.blocks(input, output, State::decrypt_block, State::decrypt_lanes);
+    }
+
+    fn finish(mut self, key: &[u8], tag: &mut [u8]) {
+        if P > 1 && self.blocks > 0 {
+            self.state = State::merge(&mut self.lanes);
+        }
+
+        self.state.finalize(key, tag);
+    }
+
+    /// `input` is shorter than a block, `output` also holds the tag.
+    pub fn encrypt_finalize(mut self, key: &[u8], input: &[u8], output: &mut [u8]) {
+        let (output, tag) = output.split_at_mut(input.len());
+
+        if self.blocks > 0 || !input.is_empty() {
+            self.lane().encrypt_lastblock(input, output);
+            self.blocks += 1;
+        }
+
+        self.finish(key, tag);
+    }
+
+    /// `input` is shorter than a block and followed by the tag.
+    pub fn decrypt_finalize(mut self, key: &[u8], input: &[u8], output: &mut [u8]) -> bool {
+        let (input, tag) = input.split_at(output.len());
+
+        if self.blocks > 0 || !input.is_empty() {
+            self.lane().decrypt_lastblock(input, output);
+            self.blocks += 1;
+        }
+
+        let mut expected = [0; MAX_TAG_LENGTH];
+        let expected = &mut expected[..tag.len()];
+        self.finish(key, expected);
+
+        expected.iter()
+            .zip(tag)
+            .fold(0, |sum, (x, y)| sum | (x ^ y)) == 0
+    }
+}
+
+
+/// Feeds whole blocks to `f` and keeps the partial block in `buf[..pos]`,
+/// `buf` is exactly one block.
+pub fn buffered<F>(buf: &mut [u8], pos: &mut usize, mut input: &[u8], output: &mut [u8], mut f: F)
+    -> Result<usize, Error>
+    where F: FnMut(&[u8], &mut [u8])
+{
+    let block_length = buf.len();
+    let len = *pos + input.len();
+    let len = len - len % block_length;
+    if output.len() < len {
+        return Err(Error::Length);
+    }
+
+    let mut take = 0;
+
+    if *pos > 0 {
+        let n = cmp::min(block_length - *pos, input.len());
+        buf[*pos..][..n].copy_from_slice(&input[..n]);
+        *pos += n;
+        input = &input[n..];
+
+        if *pos < block_length {
+            return Ok(0);
+        }
+
+        f(buf, &mut output[..block_length]);
+        *pos = 0;
+        take = block_length;
+    }
+
+    let (input, remaining) = input.split_at(input.len() - input.len() % block_length);
+    f(input, &mut output[take..][..input.len()]);
+    buf[..remaining.len()].copy_from_slice(remaining);
+    *pos = remaining.len();
+
+    Ok(len)
+}
+
+
+/// Defines a NORX cipher over `Process<$word, $p>` with its online processes,
+/// `$name` is the cipher type.
+macro_rules! norx {
+    ( $( #[$attr:meta] )* $name:ident, $word:ty, $p:expr ) => {
+        use arrayref::array_ref;
+        use crate::Error;
+        use crate::aead::norx_core::{ self, buffered, block_length, key_length };
+        use crate::aead::{ AeadCipher, Online, Encryption, Decryption };
+
+        pub const KEY_LENGTH: usize = key_length::<$word>();
+        pub const NONCE_LENGTH: usize = key_length::<$word>();
+        pub const TAG_LENGTH: usize = key_length::<$word>();
+        pub const BLOCK_LENGTH: usize = block_length::<$word>();
+
+        type Process = norx_core::Process<$word, $p>;
+
+        $( #[$attr] )*
+        pub struct $name([u8; KEY_LENGTH]);
+
+        pub struct EncryptProcess<'a> {
+            process: Process,
+            key: &'a [u8; KEY_LENGTH],
+            buf: [u8; BLOCK_LENGTH],
+            pos: usize
+        }
+
+        pub struct DecryptProcess<'a> {
+            process: Process,
+            key: &'a [u8; KEY_LENGTH],
+            buf: [u8; BLOCK_LENGTH],
+            pos: usize
+        }
+
+        impl AeadCipher for $name {
+            type Key = [u8; KEY_LENGTH];
+            type Nonce = [u8; NONCE_LENGTH];
+            type Tag = [u8; TAG_LENGTH];
+
+            fn new(key: &Self::Key) -> Self {
+                $name(*key)
+            }
+
+            fn seal(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+                self.encrypt(nonce, aad).finalize(input, output)
+            }
+
+            fn open(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+                self.decrypt(nonce, aad).finalize(input, output)
+            }
+
+            fn seal_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<Self::Tag, Error> {
+                let mut process = Process::new(&self.0, nonce, aad);
+
+                let len = buf.len() - buf.len() % BLOCK_LENGTH;
+                let (buf, remaining) = buf.split_at_mut(len);
+                let mut block = [0; BLOCK_LENGTH];
+                for chunk in buf.chunks_mut(BLOCK_LENGTH) {
+                    block.copy_from_slice(chunk);
+                    process.encrypt(&block, chunk);
+                }
+
+                let mut output = [0; BLOCK_LENGTH + TAG_LENGTH];
+                let output = &mut output[..remaining.len() + TAG_LENGTH];
+                process.encrypt_finalize(&self.0, remaining, output);
+                remaining.copy_from_slice(&output[..remaining.len()]);
+
+                Ok(*array_ref!(output, remaining.len(), TAG_LENGTH))
+            }
+
+            fn open_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8], tag: &Self::Tag) -> Result<(), Error> {
+                let mut process = Process::new(&self.0, nonce, aad);
+
+                let len = buf.len() - buf.len() % BLOCK_LENGTH;
+                let (blocks, remaining) = buf.split_at_mut(len);
+                let mut block = [0; BLOCK_LENGTH];
+                for chunk in blocks.chunks_mut(BLOCK_LENGTH) {
+                    block.copy_from_slice(chunk);
+                    process.decrypt(&block, chunk);
+                }
+
+                let mut input = [0; BLOCK_LENGTH + TAG_LENGTH];
+                let input = &mut input[..remaining.len() + TAG_LENGTH];
+                input[..remaining.len()].copy_from_slice(remaining);
+                input[remaining.len()..].copy_from_slice(tag);
+
+                if process.decrypt_finalize(&self.0, input, remaining) {
+                    Ok(())
+                } else {
+                    // don't release unauthenticated plaintext
+                    buf.iter_mut().for_each(|b| *b = 0);
+                    Err(Error::VerificationFailed)
+                }
+            }
+        }
+
+        impl<'a> Online<'a> for $name {
+            type Encryption = EncryptProcess<'a>;
+            type Decryption = DecryptProcess<'a>;
+
+            fn encrypt(&'a self, nonce: &Self::Nonce, aad: &[u8]) -> Self::Encryption {
+                EncryptProcess {
+                    process: Process::new(&self.0, nonce, aad),
+                    key: &self.0,
+                    buf: [0; BLOCK_LENGTH],
+                    pos: 0
+                }
+            }
+
+            fn decrypt(&'a self, nonce: &Self::Nonce, aad: &[u8]) -> Self::Decryption {
+                DecryptProcess {
+                    process: Process::new(&self.0, nonce, aad),
+                    key: &self.0,
+                    buf: [0; BLOCK_LENGTH],
+                    pos: 0
+                }
+            }
+        }
+
+        impl<'a> Encryption<'a> for EncryptProcess<'a> {
+            fn process(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+                let EncryptProcess { process, buf, pos, .. } = self;
+                buffered(buf, pos, input, output, |input, output| process.encrypt(input, output))
+            }
+
+            fn finalize(mut self, input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+                if self.pos + input.len() + TAG_LENGTH != output.len() {
+                    return Err(Error::Length);
+                }
+
+                let take = self.process(input, output)?;
+                let (_, output) = output.split_at_mut(take);
+                self.process.encrypt_finalize(self.key, &self.buf[..self.pos], output);
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Decryption<'a> for DecryptProcess<'a> {
+            fn process(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+                let DecryptProcess { process, buf, pos, .. } = self;
+                buffered(buf, pos, input, output, |input, output| process.decrypt(input, output))
+            }
+
+            fn finalize(mut self, input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+                if input.len() < TAG_LENGTH || self.pos + input.len() != output.len() + TAG_LENGTH {
+                    return Err(Error::Length);
+                }
+
+                let (input, tag) = input.split_at(input.len() - TAG_LENGTH);
+                let take = self.process(input, output)?;
+                let (_, output) = output.split_at_mut(take);
+
+                let mut remaining = [0; BLOCK_LENGTH + TAG_LENGTH];
+                let remaining = &mut remaining[..self.pos + TAG_LENGTH];
+                remaining[..self.pos].copy_from_slice(&self.buf[..self.pos]);
+                remaining[self.pos..].copy_from_slice(tag);
+
+                if self.process.decrypt_finalize(self.key, remaining, output) {
+                    Ok(())
+                } else {
+                    Err(Error::VerificationFailed)
+                }
+            }
+        }
+    }
+}
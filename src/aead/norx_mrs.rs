@@ -1,63 +1,205 @@
-use arrayref::{ array_ref, array_mut_ref };
+use std::io::{ self, Read, Write, Seek, SeekFrom };
+use arrayref::array_ref;
+use seckey::TempKey;
 use norx_permutation::{ U, S, norx };
 use mem_aead_mrs::{
-    KEY_LENGTH, NONCE_LENGTH, TAG_LENGTH,
+    KEY_LENGTH, NONCE_LENGTH, TAG_LENGTH, STATE_LENGTH,
     Mrs, Permutation
 };
 use crate::Error;
+use super::mrs_core::State;
 use super::AeadCipher;
 
 
+/// A multiple of both the absorption and the encryption block.
+const CHUNK_LENGTH: usize = 3 * STATE_LENGTH;
+
 pub struct NorxMRS([u8; KEY_LENGTH]);
 
+impl NorxMRS {
+    /// Two-pass sealing of `input` from its current position to the end,
+    /// writes the ciphertext followed by the tag to `output`.
+    ///
+    /// `input` is read once for the tag and again for the ciphertext,
+    /// so it must not change in between.
+    pub fn seal_stream<R, W>(&self, nonce: &[u8; NONCE_LENGTH], aad: &[u8], mut input: R, mut output: W)
+        -> io::Result<()>
+        where R: Read + Seek, W: Write
+    {
+        let NorxMRS(key) = self;
+        let mut buf = [0; CHUNK_LENGTH];
+        let mut buf = TempKey::from(&mut buf);
+        let start = input.seek(SeekFrom::Current(0))?;
+
+        let mut state = State::absorption(key, nonce);
+        state.absorb(aad);
+        let len = pass(&mut input, u64::max_value(), &mut buf[..], |chunk| {
+            state.absorb(chunk);
+            Ok(())
+        })?;
+        let tag = state.finalize(aad.len() as u64, len);
+
+        input.seek(SeekFrom::Start(start))?;
+        let mut state = State::encryption(key, &tag);
+        let len2 = pass(&mut input, len, &mut buf[..], |chunk| {
+            state.encrypt(chunk);
+            output.write_all(chunk)
+        })?;
+        if len2 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        output.write_all(&tag)
+    }
+
+    /// Two-pass opening of `input` from its current position to the end,
+    /// the ciphertext followed by the tag, writes the plaintext to `output`.
+    ///
+    /// The first pass only checks the tag, nothing is written to `output` unless it matches.
+    /// `input` is read again for the plaintext, so it must not change in between,
+    /// the second pass checks the tag again and `output` is untrusted unless this returns `Ok`.
+    pub fn open_stream<R, W>(&self, nonce: &[u8; NONCE_LENGTH], aad: &[u8], mut input: R, mut output: W)
+        -> io::Result<()>
+        where R: Read + Seek, W: Write
+    {
+        let NorxMRS(key) = self;
+        let mut buf = [0; CHUNK_LENGTH];
+        let mut buf = TempKey::from(&mut buf);
+        let start = input.seek(SeekFrom::Current(0))?;
+        let end = input.seek(SeekFrom::End(0))?;
+        let len = end.saturating_sub(start)
+            .checked_sub(TAG_LENGTH as u64)
+            .ok_or(Error::Length)?;
+
+        let mut tag = [0; TAG_LENGTH];
+        input.seek(SeekFrom::Start(start + len))?;
+        input.read_exact(&mut tag)?;
+
+        input.seek(SeekFrom::Start(start))?;
+        let mut decryption = State::encryption(key, &tag);
+        let mut absorption = State::absorption(key, nonce);
+        absorption.absorb(aad);
+        let len2 = pass(&mut input, len, &mut buf[..], |chunk| {
+            decryption.decrypt(chunk);
+            absorption.absorb(chunk);
+            Ok(())
+        })?;
+        if len2 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        if !verify(absorption, aad.len() as u64, len, &tag) {
+            return Err(Error::VerificationFailed.into());
+        }
+
+        input.seek(SeekFrom::Start(start))?;
+        let mut decryption = State::encryption(key, &tag);
+        let mut absorption = State::absorption(key, nonce);
+        absorption.absorb(aad);
+        let len2 = pass(&mut input, len, &mut buf[..], |chunk| {
+            decryption.decrypt(chunk);
+            absorption.absorb(chunk);
+            output.write_all(chunk)
+        })?;
+        if len2 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        // `input` may have changed since the first pass
+        if !verify(absorption, aad.len() as u64, len, &tag) {
+            return Err(Error::VerificationFailed.into());
+        }
+
+        Ok(())
+    }
+}
+
+fn verify(absorption: State, aad_len: u64, len: u64, tag: &[u8; TAG_LENGTH]) -> bool {
+    let expected = absorption.finalize(aad_len, len);
+    let diff = expected.iter()
+        .zip(tag)
+        .fold(0, |sum, (x, y)| sum | (x ^ y));
+    diff == 0
+}
+
+/// Feeds up to `len` bytes of `input` to `f` in `buf` sized chunks,
+/// only the last chunk can be shorter. Returns the number of bytes read.
+fn pass<R, F>(input: &mut R, len: u64, buf: &mut [u8], mut f: F) -> io::Result<u64>
+    where R: Read, F: FnMut(&mut [u8]) -> io::Result<()>
+{
+    let mut input = input.take(len);
+    let mut total = 0;
+
+    loop {
+        let mut n = 0;
+        while n < buf.len() {
+            match input.read(&mut buf[n..]) {
+                Ok(0) => break,
+                Ok(m) => n += m,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err)
+            }
+        }
+
+        f(&mut buf[..n])?;
+        total += n as u64;
+
+        if n < buf.len() {
+            return Ok(total);
+        }
+    }
+}
+
 impl AeadCipher for NorxMRS {
-    const KEY_LENGTH: usize = KEY_LENGTH;
-    const NONCE_LENGTH: usize = NONCE_LENGTH;
-    const TAG_LENGTH: usize = TAG_LENGTH;
-
-    fn new(key: &[u8]) -> Self {
-        let mut k = [0; KEY_LENGTH];
-        k.copy_from_slice(key);
-        NorxMRS(k)
+    type Key = [u8; KEY_LENGTH];
+    type Nonce = [u8; NONCE_LENGTH];
+    type Tag = [u8; TAG_LENGTH];
+
+    fn new(key: &Self::Key) -> Self {
+        NorxMRS(*key)
     }
 
-    fn seal(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
-        if nonce.len() != Self::NONCE_LENGTH ||
-            input.len() + Self::TAG_LENGTH != output.len()
-        {
+    fn seal(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        if input.len() + Self::TAG_LENGTH != output.len() {
             return Err(Error::Length);
         }
 
         let (output, tag) = output.split_at_mut(input.len());
         output.copy_from_slice(input);
-        let NorxMRS(key) = self;
-        let tag = array_mut_ref!(tag, 0, TAG_LENGTH);
-        let nonce = array_ref!(nonce, 0, NONCE_LENGTH);
-
-        Mrs::<Norx644P>::new()
-            .encrypt(key, nonce, aad, output, tag);
-
+        tag.copy_from_slice(&self.seal_in_place_detached(nonce, aad, output)?);
         Ok(())
     }
 
-    fn open(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
-        if nonce.len() != Self::NONCE_LENGTH ||
-            input.len() != output.len() + Self::TAG_LENGTH
-        {
+    fn open(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        if input.len() != output.len() + Self::TAG_LENGTH {
             return Err(Error::Length);
         }
 
         let (input, tag) = input.split_at(output.len());
         output.copy_from_slice(input);
+        self.open_in_place_detached(nonce, aad, output, array_ref!(tag, 0, TAG_LENGTH))
+    }
+
+    fn seal_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<Self::Tag, Error> {
+        let NorxMRS(key) = self;
+        let mut tag = [0; TAG_LENGTH];
+
+        Mrs::<Norx644P>::new()
+            .encrypt(key, nonce, aad, buf, &mut tag);
+
+        Ok(tag)
+    }
+
+    fn open_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8], tag: &Self::Tag) -> Result<(), Error> {
         let NorxMRS(key) = self;
-        let tag = array_ref!(tag, 0, TAG_LENGTH);
-        let nonce = array_ref!(nonce, 0, NONCE_LENGTH);
 
         if Mrs::<Norx644P>::new()
-            .decrypt(key, nonce, aad, output, tag)
+            .decrypt(key, nonce, aad, buf, tag)
         {
             Ok(())
         } else {
+            // don't release unauthenticated plaintext
+            buf.iter_mut().for_each(|b| *b = 0);
             Err(Error::VerificationFailed)
         }
     }
@@ -0,0 +1,97 @@
+//! Rekeying session, the key is ratcheted forward with HNORX (under its own domain tag) once
+//! a message or byte limit is reached.
+//!
+//! Both sides count the messages they seal or open successfully,
+//! so messages must be opened in the order they were sealed.
+
+use seckey::TempKey;
+use crate::Error;
+use super::xnorx::{ hnorx_domain, HNORX_NONCE_LENGTH, RATCHET_DOMAIN };
+use super::AeadCipher;
+
+
+/// The key is ratcheted after `messages` messages or `bytes` bytes of plaintext,
+/// whichever comes first.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub messages: u64,
+    pub bytes: u64
+}
+
+pub struct Rekeying<AE: AeadCipher<Key = [u8; 32]>> {
+    ae: AE,
+    key: [u8; 32],
+    limits: Limits,
+    epoch: u64,
+    messages: u64,
+    bytes: u64
+}
+
+impl<AE: AeadCipher<Key = [u8; 32]>> Rekeying<AE> {
+    pub fn new(key: &[u8; 32], limits: Limits) -> Self {
+        Rekeying {
+            ae: AE::new(key),
+            key: *key,
+            limits,
+            epoch: 0,
+            messages: 0,
+            bytes: 0
+        }
+    }
+
+    /// Number of ratchets so far.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn seal(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        self.ae.seal(nonce, aad, input, output)?;
+        self.account(input.len());
+        Ok(())
+    }
+
+    pub fn open(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        self.ae.open(nonce, aad, input, output)?;
+        self.account(output.len());
+        Ok(())
+    }
+
+    pub fn seal_to_vec(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        let output = self.ae.seal_to_vec(nonce, aad, input)?;
+        self.account(input.len());
+        Ok(output)
+    }
+
+    pub fn open_to_vec(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        let output = self.ae.open_to_vec(nonce, aad, input)?;
+        self.account(output.len());
+        Ok(output)
+    }
+
+    fn account(&mut self, len: usize) {
+        self.messages += 1;
+        self.bytes = self.bytes.saturating_add(len as u64);
+
+        if self.messages >= self.limits.messages || self.bytes >= self.limits.bytes {
+            self.ratchet();
+        }
+    }
+
+    fn ratchet(&mut self) {
+        let mut key = [0; 32];
+        let mut key = TempKey::from(&mut key);
+        hnorx_domain(&self.key, &[0; HNORX_NONCE_LENGTH], RATCHET_DOMAIN, &mut key);
+        self.key = *key;
+        self.ae = AE::new(&key);
+
+        self.epoch += 1;
+        self.messages = 0;
+        self.bytes = 0;
+    }
+}
+
+impl<AE: AeadCipher<Key = [u8; 32]>> Drop for Rekeying<AE> {
+    fn drop(&mut self) {
+        drop(TempKey::from(&mut self.key));
+    }
+}
@@ -0,0 +1,89 @@
+//! STREAM segmented encryption.
+//!
+//! Each segment is sealed under `prefix || counter || last`,
+//! where `counter` is a 32-bit big-endian segment number and `last` is `1` only for the final segment.
+//! Every segment is authenticated on its own, so reordering, dropping or truncating segments
+//! is detected without buffering the whole message.
+//!
+//! See [Online Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance](https://eprint.iacr.org/2015/189.pdf).
+
+use crate::{ ByteArray, Error };
+use super::AeadCipher;
+
+
+/// Counter and last-segment flag appended to the nonce prefix.
+pub const NONCE_SUFFIX_LENGTH: usize = 4 + 1;
+
+struct Stream<AE: AeadCipher> {
+    ae: AE,
+    nonce: AE::Nonce,
+    counter: u32
+}
+
+pub struct SealStream<AE: AeadCipher>(Stream<AE>);
+pub struct OpenStream<AE: AeadCipher>(Stream<AE>);
+
+impl<AE: AeadCipher> Stream<AE> {
+    fn new(ae: AE, prefix: &[u8]) -> Result<Self, Error> {
+        if prefix.len() + NONCE_SUFFIX_LENGTH != AE::NONCE_LENGTH {
+            return Err(Error::Length);
+        }
+
+        let mut nonce = AE::Nonce::zero();
+        nonce.as_mut()[..prefix.len()].copy_from_slice(prefix);
+
+        Ok(Stream { ae, nonce, counter: 0 })
+    }
+
+    fn set_nonce(&mut self, last: bool) {
+        let (_, suffix) = self.nonce.as_mut().split_at_mut(AE::NONCE_LENGTH - NONCE_SUFFIX_LENGTH);
+        suffix[..4].copy_from_slice(&self.counter.to_be_bytes());
+        suffix[4] = last as u8;
+    }
+
+    /// The last segment may use the final counter value,
+    /// so a non-last segment always needs room for one more.
+    fn next_counter(&self) -> Result<u32, Error> {
+        self.counter.checked_add(1).ok_or(Error::NonceExhausted)
+    }
+}
+
+impl<AE: AeadCipher> SealStream<AE> {
+    /// `prefix` must be `AE::NONCE_LENGTH - NONCE_SUFFIX_LENGTH` bytes and unique per key.
+    pub fn new(ae: AE, prefix: &[u8]) -> Result<Self, Error> {
+        Stream::new(ae, prefix).map(SealStream)
+    }
+
+    pub fn seal(&mut self, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        let counter = self.0.next_counter()?;
+        self.0.set_nonce(false);
+        self.0.ae.seal(&self.0.nonce, aad, input, output)?;
+        self.0.counter = counter;
+        Ok(())
+    }
+
+    pub fn seal_last(mut self, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        self.0.set_nonce(true);
+        self.0.ae.seal(&self.0.nonce, aad, input, output)
+    }
+}
+
+impl<AE: AeadCipher> OpenStream<AE> {
+    pub fn new(ae: AE, prefix: &[u8]) -> Result<Self, Error> {
+        Stream::new(ae, prefix).map(OpenStream)
+    }
+
+    pub fn open(&mut self, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        let counter = self.0.next_counter()?;
+        self.0.set_nonce(false);
+        self.0.ae.open(&self.0.nonce, aad, input, output)?;
+        self.0.counter = counter;
+        Ok(())
+    }
+
+    /// Must be called on the final segment, otherwise truncation goes unnoticed.
+    pub fn open_last(mut self, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        self.0.set_nonce(true);
+        self.0.ae.open(&self.0.nonce, aad, input, output)
+    }
+}
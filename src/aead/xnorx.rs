@@ -0,0 +1,95 @@
+//! Extended-nonce NORX, HNORX derives a subkey from the key and the first
+//! `HNORX_NONCE_LENGTH` bytes of the nonce, the rest is the `Norx6441` nonce.
+//!
+//! The construction follows HChaCha/XChaCha. `Norx6441` already takes a 256-bit nonce,
+//! so random 32-byte nonces are fine there too, what this adds is a fresh subkey per
+//! HNORX nonce, the NORX key itself never touches a message.
+
+use arrayref::array_refs;
+use seckey::TempKey;
+use norx_permutation::{ U, S, norx };
+use norx::constant::{ KEY_LENGTH, NONCE_LENGTH, TAG_LENGTH };
+use crate::Error;
+use super::norx_core::Word;
+use super::norx6441::Norx6441;
+use super::AeadCipher;
+
+
+pub const HNORX_NONCE_LENGTH: usize = 4 * 8;
+
+/// Domain tags for `state[15]`, keeping the HNORX uses apart from each other
+/// and from the NORX initialization.
+pub(crate) const XNORX_DOMAIN: U = 0x40;
+pub(crate) const COMMITTING_KEY_DOMAIN: U = 0x80;
+pub(crate) const COMMITMENT_DOMAIN: U = 0x100;
+pub(crate) const RATCHET_DOMAIN: U = 0x200;
+
+/// `key` and `nonce` fill the state as in NORX, the constants come from `F^2(0, 1, .., 15)`,
+/// after `F^2L` the words that held the nonce are the subkey.
+pub fn hnorx(key: &[u8; KEY_LENGTH], nonce: &[u8; HNORX_NONCE_LENGTH], subkey: &mut [u8; KEY_LENGTH]) {
+    hnorx_domain(key, nonce, XNORX_DOMAIN, subkey)
+}
+
+pub(crate) fn hnorx_domain(key: &[u8; KEY_LENGTH], nonce: &[u8; HNORX_NONCE_LENGTH], domain: U, subkey: &mut [u8; KEY_LENGTH]) {
+    let mut state = [0; S];
+
+    for i in 0..4 {
+        state[i] = U::load(&nonce[i * 8..]);
+        state[i + 4] = U::load(&key[i * 8..]);
+    }
+    state[8..].copy_from_slice(&U::INIT);
+    state[15] ^= domain;
+
+    norx(&mut state);
+    norx(&mut state);
+
+    for i in 0..4 {
+        state[i].store(&mut subkey[i * 8..]);
+    }
+}
+
+
+// TODO `Online`, the processes of `Norx6441` borrow its key
+pub struct XNorx6441([u8; KEY_LENGTH]);
+
+impl XNorx6441 {
+    fn subcipher<'a>(&self, nonce: &'a <Self as AeadCipher>::Nonce) -> (Norx6441, &'a [u8; NONCE_LENGTH]) {
+        let (hnonce, nonce) = array_refs![nonce, HNORX_NONCE_LENGTH, NONCE_LENGTH];
+        let mut subkey = [0; KEY_LENGTH];
+        let mut subkey = TempKey::from(&mut subkey);
+        hnorx(&self.0, hnonce, &mut subkey);
+        let ae = Norx6441::new(&subkey);
+
+        (ae, nonce)
+    }
+}
+
+impl AeadCipher for XNorx6441 {
+    type Key = [u8; KEY_LENGTH];
+    type Nonce = [u8; HNORX_NONCE_LENGTH + NONCE_LENGTH];
+    type Tag = [u8; TAG_LENGTH];
+
+    fn new(key: &Self::Key) -> Self {
+        XNorx6441(*key)
+    }
+
+    fn seal(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        let (ae, nonce) = self.subcipher(nonce);
+        ae.seal(nonce, aad, input, output)
+    }
+
+    fn open(&self, nonce: &Self::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        let (ae, nonce) = self.subcipher(nonce);
+        ae.open(nonce, aad, input, output)
+    }
+
+    fn seal_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8]) -> Result<Self::Tag, Error> {
+        let (ae, nonce) = self.subcipher(nonce);
+        ae.seal_in_place_detached(nonce, aad, buf)
+    }
+
+    fn open_in_place_detached(&self, nonce: &Self::Nonce, aad: &[u8], buf: &mut [u8], tag: &Self::Tag) -> Result<(), Error> {
+        let (ae, nonce) = self.subcipher(nonce);
+        ae.open_in_place_detached(nonce, aad, buf, tag)
+    }
+}
@@ -13,7 +13,7 @@ macro_rules! eq {
 macro_rules! packing {
     ( $t:ident ; $len:expr ) => {
         impl Packing for $t {
-            const BYTES_LENGTH: usize = $len;
+            type Bytes = [u8; $len];
 
             fn read_bytes<T, F>(&self, f: F)
                 -> T
@@ -22,11 +22,8 @@ macro_rules! packing {
                 f(&self.0)
             }
 
-            fn from_bytes(buf: &[u8]) -> Self {
-                let buf = arrayref::array_ref!(buf, 0, $len);
-                let mut pk = [0; $len];
-                pk.clone_from(buf);
-                $t(pk)
+            fn from_bytes(buf: &Self::Bytes) -> Self {
+                $t(*buf)
             }
         }
     };
@@ -60,7 +57,9 @@ macro_rules! serde {
                         where E: de::Error
                     {
                         if v.len() == $t::BYTES_LENGTH {
-                            Ok($t::from_bytes(v))
+                            let mut buf = <<$t as Packing>::Bytes as crate::ByteArray>::zero();
+                            buf.as_mut().copy_from_slice(v);
+                            Ok($t::from_bytes(&buf))
                         } else {
                             Err(de::Error::invalid_length(v.len(), &self))
                         }
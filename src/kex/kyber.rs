@@ -1,8 +1,9 @@
-use arrayref::array_mut_ref;
 use rand::{ Rng, CryptoRng };
 use kyber::{ params, kem };
-use crate::{ Packing, Error };
-use super::{ KeyExchange, CheckedExchange };
+use seckey::TempKey;
+use crate::{ Packing, Error, SEED_LENGTH };
+use crate::seed::{ self, SeedRng };
+use super::{ KeyExchange, CheckedExchange, SeedableExchange };
 
 
 pub struct Kyber;
@@ -14,8 +15,7 @@ impl KeyExchange for Kyber {
     type PrivateKey = PrivateKey;
     type PublicKey = PublicKey;
     type Message = Message;
-
-    const SHARED_LENGTH: usize = params::SYMBYTES;
+    type SharedKey = [u8; params::SYMBYTES];
 
     fn keypair<R: Rng + CryptoRng>(mut r: R) -> (Self::PrivateKey, Self::PublicKey) {
         let mut sk = [0; params::SECRETKEYBYTES];
@@ -25,25 +25,23 @@ impl KeyExchange for Kyber {
         (PrivateKey(sk), PublicKey(pk))
     }
 
-    fn exchange_to<R: Rng + CryptoRng>(mut r: R, sharedkey: &mut [u8], &PublicKey(ref pk): &Self::PublicKey) -> Self::Message {
-        let sharedkey = array_mut_ref!(sharedkey, 0, params::SYMBYTES);
+    fn exchange_to<R: Rng + CryptoRng>(mut r: R, sharedkey: &mut Self::SharedKey, &PublicKey(ref pk): &Self::PublicKey) -> Self::Message {
         let mut c = [0; params::CIPHERTEXTBYTES];
         kem::enc(&mut r, &mut c, sharedkey, pk);
         Message(c)
     }
 
-    fn exchange_from(sharedkey: &mut [u8], sk: &Self::PrivateKey, m: &Self::Message) {
+    fn exchange_from(sharedkey: &mut Self::SharedKey, sk: &Self::PrivateKey, m: &Self::Message) {
         let _ = <Kyber as CheckedExchange>::exchange_from(sharedkey, sk, m);
     }
 }
 
 impl CheckedExchange for Kyber {
     fn exchange_from(
-        sharedkey: &mut [u8],
+        sharedkey: &mut Self::SharedKey,
         &PrivateKey(ref sk): &Self::PrivateKey,
         &Message(ref m): &Self::Message
     ) -> Result<(), Error> {
-        let sharedkey = array_mut_ref!(sharedkey, 0, params::SYMBYTES);
         if kem::dec(sharedkey, m, sk) {
             Ok(())
         } else {
@@ -52,6 +50,17 @@ impl CheckedExchange for Kyber {
     }
 }
 
+impl SeedableExchange for Kyber {
+    /// `seed` is expanded with SHAKE256 to the key generation seed `d`
+    /// and the implicit rejection key `z`, in the order Kyber reads them.
+    fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey) {
+        let mut dz = [0; 2 * params::SYMBYTES];
+        let mut dz = TempKey::from(&mut dz);
+        seed::expand(seed, &mut dz[..]);
+        Kyber::keypair(SeedRng(&dz[..]))
+    }
+}
+
 eq!(PrivateKey);
 eq!(PublicKey);
 eq!(Message);
@@ -0,0 +1,195 @@
+//! ML-KEM, FIPS 203.
+//!
+//! Decapsulation uses implicit rejection, an invalid `Message` gives a pseudorandom
+//! shared key instead of an error, so `CheckedExchange::exchange_from` always returns `Ok`.
+//!
+//! `exchange_to` does the FIPS 203 encapsulation key check and panics
+//! on a `PublicKey` that does not decode to itself.
+
+macro_rules! mlkem {
+    ( $name:ident, $params:ident, $set:expr ; $pk:expr, $sk:expr, $ct:expr ) => {
+        pub mod $name {
+            use std::convert::TryFrom;
+            use arrayref::array_ref;
+            use seckey::TempKey;
+            use rand::{ Rng, CryptoRng };
+            use ml_kem::{ $params, KemCore, EncodedSizeUser, EncapsulateDeterministic, Encoded, Ciphertext };
+            use ml_kem::kem::Decapsulate;
+            use crate::{ Packing, Error };
+            use crate::kex::{ KeyExchange, CheckedExchange, SeedableExchange, SEED_LENGTH };
+
+            pub const PUBLICKEYBYTES: usize = $pk;
+            pub const SECRETKEYBYTES: usize = $sk;
+            pub const CIPHERTEXTBYTES: usize = $ct;
+            pub const SHAREDKEYBYTES: usize = 32;
+
+            type DecapsulationKey = <$params as KemCore>::DecapsulationKey;
+            type EncapsulationKey = <$params as KemCore>::EncapsulationKey;
+
+            pub struct MlKem;
+            pub struct PrivateKey([u8; SECRETKEYBYTES]);
+            pub struct PublicKey([u8; PUBLICKEYBYTES]);
+            pub struct Message([u8; CIPHERTEXTBYTES]);
+
+            impl MlKem {
+                /// `dz` is the FIPS 203 key generation seeds `d || z`.
+                fn keygen(dz: &[u8; 64]) -> (PrivateKey, PublicKey) {
+                    let d = *array_ref!(dz, 0, 32);
+                    let z = *array_ref!(dz, 32, 32);
+                    let (dk, ek) = $params::generate_deterministic(&d.into(), &z.into());
+                    let mut sk = [0; SECRETKEYBYTES];
+                    let mut pk = [0; PUBLICKEYBYTES];
+                    sk.copy_from_slice(&dk.as_bytes());
+                    pk.copy_from_slice(&ek.as_bytes());
+                    (PrivateKey(sk), PublicKey(pk))
+                }
+
+                fn encapsulate(m: &[u8; 32], sharedkey: &mut [u8; SHAREDKEYBYTES], pk: &[u8; PUBLICKEYBYTES]) -> Message {
+                    let ek = Encoded::<EncapsulationKey>::try_from(&pk[..])
+                        .expect("the public key length is always correct");
+                    let ek = EncapsulationKey::from_bytes(&ek);
+                    assert!(ek.as_bytes()[..] == pk[..], "the public key is not a valid encapsulation key");
+
+                    let (ct, key) = ek.encapsulate_deterministic(&(*m).into())
+                        .expect("encapsulation is infallible");
+
+                    let mut c = [0; CIPHERTEXTBYTES];
+                    c.copy_from_slice(&ct);
+                    sharedkey.copy_from_slice(&key);
+                    Message(c)
+                }
+            }
+
+            impl KeyExchange for MlKem {
+                type PrivateKey = PrivateKey;
+                type PublicKey = PublicKey;
+                type Message = Message;
+                type SharedKey = [u8; SHAREDKEYBYTES];
+
+                fn keypair<R: Rng + CryptoRng>(mut r: R) -> (Self::PrivateKey, Self::PublicKey) {
+                    let mut dz = [0; 64];
+                    let mut dz = TempKey::from(&mut dz);
+                    r.fill_bytes(&mut dz[..]);
+                    MlKem::keygen(&dz)
+                }
+
+                fn exchange_to<R: Rng + CryptoRng>(mut r: R, sharedkey: &mut Self::SharedKey, &PublicKey(ref pk): &Self::PublicKey) -> Self::Message {
+                    let mut m = [0; 32];
+                    let mut m = TempKey::from(&mut m);
+                    r.fill_bytes(&mut m[..]);
+                    MlKem::encapsulate(&m, sharedkey, pk)
+                }
+
+                fn exchange_from(sharedkey: &mut Self::SharedKey, sk: &Self::PrivateKey, m: &Self::Message) {
+                    let _ = <MlKem as CheckedExchange>::exchange_from(sharedkey, sk, m);
+                }
+            }
+
+            impl CheckedExchange for MlKem {
+                fn exchange_from(
+                    sharedkey: &mut Self::SharedKey,
+                    &PrivateKey(ref sk): &Self::PrivateKey,
+                    &Message(ref m): &Self::Message
+                ) -> Result<(), Error> {
+                    let dk = Encoded::<DecapsulationKey>::try_from(&sk[..])
+                        .expect("the private key length is always correct");
+                    let dk = DecapsulationKey::from_bytes(&dk);
+                    let ct = Ciphertext::<$params>::try_from(&m[..])
+                        .expect("the message length is always correct");
+                    let key = dk.decapsulate(&ct)
+                        .expect("decapsulation is infallible");
+
+                    sharedkey.copy_from_slice(&key);
+                    Ok(())
+                }
+            }
+
+            impl SeedableExchange for MlKem {
+                /// `seed` is expanded with SHAKE256 to `d || z`.
+                fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey) {
+                    let mut dz = [0; 64];
+                    let mut dz = TempKey::from(&mut dz);
+                    crate::seed::expand(seed, &mut dz[..]);
+                    MlKem::keygen(&dz)
+                }
+            }
+
+            eq!(PrivateKey);
+            eq!(PublicKey);
+            eq!(Message);
+            packing!(PrivateKey; SECRETKEYBYTES);
+            packing!(PublicKey; PUBLICKEYBYTES);
+            packing!(Message; CIPHERTEXTBYTES);
+
+            #[cfg(feature = "serde")]
+            mod serde1 {
+                use std::fmt;
+                use serde::{
+                    Serialize, Serializer, Deserialize, Deserializer,
+                    de::{ self, Visitor }
+                };
+                use super::*;
+
+                serde!(PrivateKey);
+                serde!(PublicKey);
+                serde!(Message);
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use crate::kex::KeyExchange;
+                use crate::acvp::{ vectors, hex };
+                use super::*;
+
+                #[test]
+                fn test_acvp_keygen() {
+                    for v in vectors(include_str!("../../tests/acvp/mlkem_keygen.txt"), $set) {
+                        let mut dz = hex(v["d"]);
+                        dz.extend(hex(v["z"]));
+                        let (PrivateKey(sk), PublicKey(pk)) = MlKem::keygen(array_ref!(dz, 0, 64));
+                        assert_eq!(&sk[..], &hex(v["dk"])[..], "tcId {}", v["tcId"]);
+                        assert_eq!(&pk[..], &hex(v["ek"])[..], "tcId {}", v["tcId"]);
+                    }
+                }
+
+                #[test]
+                fn test_acvp_encap() {
+                    for v in vectors(include_str!("../../tests/acvp/mlkem_encap.txt"), $set) {
+                        let pk = <[u8; PUBLICKEYBYTES]>::try_from(&hex(v["ek"])[..]).unwrap();
+                        let m = <[u8; 32]>::try_from(&hex(v["m"])[..]).unwrap();
+                        let mut sharedkey = [0; SHAREDKEYBYTES];
+                        let Message(c) = MlKem::encapsulate(&m, &mut sharedkey, &pk);
+                        assert_eq!(&c[..], &hex(v["c"])[..], "tcId {}", v["tcId"]);
+                        assert_eq!(&sharedkey[..], &hex(v["k"])[..], "tcId {}", v["tcId"]);
+                    }
+                }
+
+                #[test]
+                fn test_acvp_decap() {
+                    for v in vectors(include_str!("../../tests/acvp/mlkem_decap.txt"), $set) {
+                        let sk = <[u8; SECRETKEYBYTES]>::try_from(&hex(v["dk"])[..]).unwrap();
+                        let c = <[u8; CIPHERTEXTBYTES]>::try_from(&hex(v["c"])[..]).unwrap();
+                        let mut sharedkey = [0; SHAREDKEYBYTES];
+                        <MlKem as KeyExchange>::exchange_from(&mut sharedkey, &PrivateKey(sk), &Message(c));
+                        assert_eq!(&sharedkey[..], &hex(v["k"])[..], "tcId {}", v["tcId"]);
+                    }
+                }
+
+                #[test]
+                #[should_panic(expected = "not a valid encapsulation key")]
+                fn test_encapsulation_key_check() {
+                    let (_, PublicKey(mut pk)) = MlKem::keygen(&[0; 64]);
+                    // the first coefficient is 0xfff, not below q
+                    pk[0] = 0xff;
+                    pk[1] |= 0x0f;
+                    let mut sharedkey = [0; SHAREDKEYBYTES];
+                    MlKem::encapsulate(&[0; 32], &mut sharedkey, &pk);
+                }
+            }
+        }
+    }
+}
+
+mlkem!(mlkem512, MlKem512, "ML-KEM-512"; 800, 1632, 768);
+mlkem!(mlkem768, MlKem768, "ML-KEM-768"; 1184, 2400, 1088);
+mlkem!(mlkem1024, MlKem1024, "ML-KEM-1024"; 1568, 3168, 1568);
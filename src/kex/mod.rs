@@ -1,25 +1,33 @@
 use rand::{ Rng, CryptoRng };
-use crate::{ Packing, Error };
+use crate::{ Packing, ByteArray, Error };
 
 pub mod kyber;
+pub mod mlkem;
+
+pub use crate::SEED_LENGTH;
 
 
 pub trait KeyExchange {
     type PrivateKey: Packing;
     type PublicKey: Packing;
     type Message: Packing;
+    type SharedKey: ByteArray;
 
-    const SHARED_LENGTH: usize;
+    const SHARED_LENGTH: usize = <Self::SharedKey as ByteArray>::LENGTH;
 
     fn keypair<R: Rng + CryptoRng>(r: R) -> (Self::PrivateKey, Self::PublicKey);
 
-    /// TODO should be `sharedkey: &mut [u8; Self::SHARED_LENGTH]`
-    fn exchange_to<R: Rng + CryptoRng>(r: R, sharedkey: &mut [u8], pk: &Self::PublicKey) -> Self::Message;
+    fn exchange_to<R: Rng + CryptoRng>(r: R, sharedkey: &mut Self::SharedKey, pk: &Self::PublicKey) -> Self::Message;
 
-    /// TODO should be `sharedkey: &mut [u8; Self::SHARED_LENGTH]`
-    fn exchange_from(sharedkey: &mut [u8], sk: &Self::PrivateKey, m: &Self::Message);
+    fn exchange_from(sharedkey: &mut Self::SharedKey, sk: &Self::PrivateKey, m: &Self::Message);
 }
 
 pub trait CheckedExchange: KeyExchange {
-    fn exchange_from(sharedkey: &mut [u8], sk: &Self::PrivateKey, m: &Self::Message) -> Result<(), Error>;
+    fn exchange_from(sharedkey: &mut Self::SharedKey, sk: &Self::PrivateKey, m: &Self::Message) -> Result<(), Error>;
+}
+
+/// Key exchanges whose key generation can be driven by a seed.
+pub trait SeedableExchange: KeyExchange {
+    /// The same `seed` always gives the same keypair.
+    fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey);
 }
@@ -0,0 +1,55 @@
+//! Deterministic key wrapping on top of `NorxMRS`.
+//!
+//! MRS is misuse-resistant, so the nonce is fixed and the same value
+//! always wraps to the same bytes under the same key and aad.
+//! The length of the wrapped value is authenticated along with `aad`.
+
+use seckey::TempKey;
+use crate::aead::AeadCipher;
+use crate::aead::norx_mrs::NorxMRS;
+use crate::{ Packing, ByteArray, Error };
+
+
+const LABEL: &[u8] = b"sarkara keywrap";
+
+pub struct KeyWrap(NorxMRS);
+
+impl KeyWrap {
+    /// Wrapped values are `P::BYTES_LENGTH + OVERHEAD` bytes.
+    pub const OVERHEAD: usize = NorxMRS::TAG_LENGTH;
+
+    pub fn new(kek: &<NorxMRS as AeadCipher>::Key) -> Self {
+        KeyWrap(NorxMRS::new(kek))
+    }
+
+    pub fn wrap<P: Packing>(&self, aad: &[u8], value: &P) -> Vec<u8> {
+        let nonce = [0; NorxMRS::NONCE_LENGTH];
+        let aad = header::<P>(aad);
+
+        value.read_bytes(|bytes| self.0.seal_to_vec(&nonce, &aad, bytes))
+            .expect("the output length is always correct")
+    }
+
+    pub fn unwrap<P: Packing>(&self, aad: &[u8], wrapped: &[u8]) -> Result<P, Error> {
+        if wrapped.len() != P::BYTES_LENGTH + Self::OVERHEAD {
+            return Err(Error::Length);
+        }
+
+        let nonce = [0; NorxMRS::NONCE_LENGTH];
+        let aad = header::<P>(aad);
+
+        let mut output = P::Bytes::zero();
+        let mut output = TempKey::from(&mut output);
+        self.0.open(&nonce, &aad, wrapped, output.as_mut())?;
+
+        Ok(P::from_bytes(&output))
+    }
+}
+
+fn header<P: Packing>(aad: &[u8]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(LABEL.len() + 8 + aad.len());
+    header.extend_from_slice(LABEL);
+    header.extend_from_slice(&(P::BYTES_LENGTH as u64).to_be_bytes());
+    header.extend_from_slice(aad);
+    header
+}
@@ -10,23 +10,82 @@ pub mod sign;
 pub mod kex;
 pub mod aead;
 pub mod sealedbox;
+pub mod keywrap;
+mod seed;
+#[cfg(test)] mod acvp;
 
+use std::{ io, slice };
 use failure::Fail;
 
 
+/// Length of the seed taken by `SeedableSignature` and `SeedableExchange`.
+pub const SEED_LENGTH: usize = 32;
+
+
 pub trait Packing: Sized {
-    const BYTES_LENGTH: usize;
+    type Bytes: ByteArray;
+
+    const BYTES_LENGTH: usize = <Self::Bytes as ByteArray>::LENGTH;
 
     fn read_bytes<T, F>(&self, f: F)
         -> T
         where F: FnOnce(&[u8]) -> T;
 
-    /// TODO should be `from_bytes(buf: &[u8; Self::LENGTH]) -> Self`
-    fn from_bytes(buf: &[u8]) -> Self;
+    fn from_bytes(buf: &Self::Bytes) -> Self;
+}
+
+/// Fixed-size keys, nonces, tags and shared secrets.
+///
+/// # Safety
+///
+/// `Self` must be exactly `LENGTH` bytes with alignment 1, no padding,
+/// and `as_ref`/`as_mut` must cover all of it.
+pub unsafe trait ByteArray: AsRef<[u8]> + AsMut<[u8]> + Clone + Send + Sync + 'static {
+    const LENGTH: usize;
+
+    fn zero() -> Self;
+}
+
+unsafe impl<const N: usize> ByteArray for [u8; N] {
+    const LENGTH: usize = N;
+
+    #[inline]
+    fn zero() -> Self {
+        [0; N]
+    }
+}
+
+/// `A || B` as a single `ByteArray`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct Concat<A, B>(pub A, pub B);
+
+impl<A: ByteArray, B: ByteArray> AsRef<[u8]> for Concat<A, B> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        // safe because `A` and `B` are plain bytes with alignment 1, see `ByteArray`
+        unsafe { slice::from_raw_parts(self as *const Self as *const u8, Self::LENGTH) }
+    }
+}
+
+impl<A: ByteArray, B: ByteArray> AsMut<[u8]> for Concat<A, B> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self as *mut Self as *mut u8, Self::LENGTH) }
+    }
+}
+
+unsafe impl<A: ByteArray, B: ByteArray> ByteArray for Concat<A, B> {
+    const LENGTH: usize = A::LENGTH + B::LENGTH;
+
+    #[inline]
+    fn zero() -> Self {
+        Concat(A::zero(), B::zero())
+    }
 }
 
 
-#[derive(Debug, Fail)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Fail)]
 #[non_exhaustive]
 #[must_use]
 pub enum Error {
@@ -35,4 +94,22 @@ pub enum Error {
 
     #[fail(display = "Fail to pass verification")]
     VerificationFailed,
+
+    #[fail(display = "Nonce sequence is exhausted")]
+    NonceExhausted,
+
+    #[fail(display = "Stateful key is exhausted")]
+    KeyExhausted,
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        let kind = match err {
+            Error::Length => io::ErrorKind::InvalidInput,
+            Error::VerificationFailed => io::ErrorKind::InvalidData,
+            Error::NonceExhausted | Error::KeyExhausted => io::ErrorKind::Other
+        };
+
+        io::Error::new(kind, err.compat())
+    }
 }
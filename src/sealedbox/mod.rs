@@ -3,55 +3,83 @@ use rand::{ Rng, CryptoRng };
 use seckey::TempKey;
 use crate::kex::{ KeyExchange, CheckedExchange };
 use crate::aead::{ AeadCipher, Online };
-use crate::Error;
+use crate::aead::rekey::{ Rekeying, Limits };
+use crate::{ ByteArray, Error };
 
 
 pub struct SealedBox<KEX, AE>(PhantomData<(KEX, AE)>);
 pub struct Sealing<AE: AeadCipher>(AE);
 pub struct Opening<AE: AeadCipher>(AE);
+pub struct RekeySealing<AE: AeadCipher<Key = [u8; 32]>>(Rekeying<AE>);
+pub struct RekeyOpening<AE: AeadCipher<Key = [u8; 32]>>(Rekeying<AE>);
 
 
 impl<KEX, AE> SealedBox<KEX, AE>
     where
         KEX: KeyExchange,
-        AE: AeadCipher,
-//        AE::KEY_LENGTH = KEX::SHARED_LENGTH
+        AE: AeadCipher<Key = KEX::SharedKey>
 {
     pub fn send<R: Rng + CryptoRng>(r: R, pk: &KEX::PublicKey) -> (KEX::Message, Sealing<AE>) {
-        // TODO static assert
-        assert_eq!(KEX::SHARED_LENGTH, AE::KEY_LENGTH);
-        let mut sharedkey: Vec<u8> = vec![0; KEX::SHARED_LENGTH];
-        let mut sharedkey = TempKey::from(&mut sharedkey as &mut [u8]);
-
-        let m = KEX::exchange_to(r, &mut sharedkey, pk);
-        let ae = AE::new(&sharedkey);
-
+        let (m, ae) = Self::exchange_to(r, pk, AE::new);
         (m, Sealing(ae))
     }
 
     pub fn recv(sk: &KEX::PrivateKey, m: &KEX::Message) -> Opening<AE> {
-        // TODO static assert
-        assert_eq!(KEX::SHARED_LENGTH, AE::KEY_LENGTH);
-        let mut sharedkey: Vec<u8> = vec![0; KEX::SHARED_LENGTH];
-        let mut sharedkey = TempKey::from(&mut sharedkey as &mut [u8]);
+        let mut sharedkey = KEX::SharedKey::zero();
+        let mut sharedkey = TempKey::from(&mut sharedkey);
 
         KEX::exchange_from(&mut sharedkey, sk, m);
         let ae = AE::new(&sharedkey);
 
         Opening(ae)
     }
+
+    fn exchange_to<R, T, F>(r: R, pk: &KEX::PublicKey, f: F) -> (KEX::Message, T)
+        where
+            R: Rng + CryptoRng,
+            F: FnOnce(&KEX::SharedKey) -> T
+    {
+        let mut sharedkey = KEX::SharedKey::zero();
+        let mut sharedkey = TempKey::from(&mut sharedkey);
+
+        let m = KEX::exchange_to(r, &mut sharedkey, pk);
+
+        (m, f(&sharedkey))
+    }
+}
+
+impl<KEX, AE> SealedBox<KEX, AE>
+    where
+        KEX: KeyExchange<SharedKey = [u8; 32]>,
+        AE: AeadCipher<Key = [u8; 32]>
+{
+    /// Like `send`, but the key is ratcheted forward according to `limits`.
+    pub fn send_rekeying<R: Rng + CryptoRng>(r: R, pk: &KEX::PublicKey, limits: Limits)
+        -> (KEX::Message, RekeySealing<AE>)
+    {
+        let (m, ae) = Self::exchange_to(r, pk, |key| Rekeying::new(key, limits));
+        (m, RekeySealing(ae))
+    }
+
+    /// `limits` must match the sender.
+    pub fn recv_rekeying(sk: &KEX::PrivateKey, m: &KEX::Message, limits: Limits) -> RekeyOpening<AE> {
+        let mut sharedkey = [0; 32];
+        let mut sharedkey = TempKey::from(&mut sharedkey);
+
+        KEX::exchange_from(&mut sharedkey, sk, m);
+
+        RekeyOpening(Rekeying::new(&sharedkey, limits))
+    }
 }
 
 impl<KEX, AE> SealedBox<KEX, AE>
     where
         KEX: CheckedExchange,
-        AE: AeadCipher
+        AE: AeadCipher<Key = KEX::SharedKey>
 {
     pub fn checked_recv(sk: &KEX::PrivateKey, m: &KEX::Message) -> Result<Opening<AE>, Error> {
-        // TODO static assert
-        assert_eq!(KEX::SHARED_LENGTH, AE::KEY_LENGTH);
-        let mut sharedkey: Vec<u8> = vec![0; KEX::SHARED_LENGTH];
-        let mut sharedkey = TempKey::from(&mut sharedkey as &mut [u8]);
+        let mut sharedkey = KEX::SharedKey::zero();
+        let mut sharedkey = TempKey::from(&mut sharedkey);
 
         <KEX as CheckedExchange>::exchange_from(&mut sharedkey, sk, m)?;
         let ae = AE::new(&sharedkey);
@@ -62,28 +90,72 @@ impl<KEX, AE> SealedBox<KEX, AE>
 
 impl<AE: AeadCipher> Sealing<AE> {
     #[inline]
-    pub fn seal(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+    pub fn seal(&self, nonce: &AE::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
         self.0.seal(nonce, aad, input, output)
     }
+
+    #[inline]
+    pub fn seal_to_vec(&self, nonce: &AE::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.seal_to_vec(nonce, aad, input)
+    }
 }
 
 impl<'a, AE: AeadCipher + Online<'a>> Sealing<AE> {
     #[inline]
-    pub fn encrypt(&'a self, nonce: &[u8], aad: &[u8]) -> AE::Encryption {
+    pub fn encrypt(&'a self, nonce: &AE::Nonce, aad: &[u8]) -> AE::Encryption {
         self.0.encrypt(nonce, aad)
     }
 }
 
 impl<AE: AeadCipher> Opening<AE> {
     #[inline]
-    pub fn open(&self, nonce: &[u8], aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+    pub fn open(&self, nonce: &AE::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
         self.0.open(nonce, aad, input, output)
     }
+
+    #[inline]
+    pub fn open_to_vec(&self, nonce: &AE::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.open_to_vec(nonce, aad, input)
+    }
 }
 
 impl<'a, AE: AeadCipher + Online<'a>> Opening<AE> {
     #[inline]
-    pub fn decrypt(&'a self, nonce: &[u8], aad: &[u8]) -> AE::Decryption {
+    pub fn decrypt(&'a self, nonce: &AE::Nonce, aad: &[u8]) -> AE::Decryption {
         self.0.decrypt(nonce, aad)
     }
 }
+
+impl<AE: AeadCipher<Key = [u8; 32]>> RekeySealing<AE> {
+    #[inline]
+    pub fn epoch(&self) -> u64 {
+        self.0.epoch()
+    }
+
+    #[inline]
+    pub fn seal(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        self.0.seal(nonce, aad, input, output)
+    }
+
+    #[inline]
+    pub fn seal_to_vec(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.seal_to_vec(nonce, aad, input)
+    }
+}
+
+impl<AE: AeadCipher<Key = [u8; 32]>> RekeyOpening<AE> {
+    #[inline]
+    pub fn epoch(&self) -> u64 {
+        self.0.epoch()
+    }
+
+    #[inline]
+    pub fn open(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        self.0.open(nonce, aad, input, output)
+    }
+
+    #[inline]
+    pub fn open_to_vec(&mut self, nonce: &AE::Nonce, aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.open_to_vec(nonce, aad, input)
+    }
+}
@@ -0,0 +1,48 @@
+//! Key generation from a `SEED_LENGTH` seed.
+
+use rand::{ RngCore, CryptoRng };
+use sha3::Shake256;
+use digest::{ Input, ExtendableOutput, XofReader };
+use crate::SEED_LENGTH;
+
+
+/// `SHAKE256(seed)`, for schemes whose key generation seed is not `SEED_LENGTH` bytes.
+pub fn expand(seed: &[u8; SEED_LENGTH], output: &mut [u8]) {
+    let mut hasher = Shake256::default();
+    hasher.process(seed);
+    hasher.xof_result().read(output);
+}
+
+/// Hands out its bytes in order, for key generation that only takes an `Rng`.
+///
+/// Reading past the end is a bug in the caller and panics.
+pub struct SeedRng<'a>(pub &'a [u8]);
+
+impl<'a> RngCore for SeedRng<'a> {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0; 4];
+        self.fill_bytes(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert!(dest.len() <= self.0.len(), "key generation read past its seed");
+
+        let (head, tail) = self.0.split_at(dest.len());
+        dest.copy_from_slice(head);
+        self.0 = tail;
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<'a> CryptoRng for SeedRng<'a> {}
@@ -0,0 +1,203 @@
+//! Composite signatures, valid only if both component signatures are valid.
+//!
+//! Keys and signatures are the concatenation of the components, `S1` first,
+//! the private key is followed by the public key.
+//!
+//! Both schemes sign the same message, the composite prefix
+//! `"sarkara-composite" || len(S1::NAME) || S1::NAME || len(S2::NAME) || S2::NAME || pk`
+//! followed by `data`, so a component signature does not verify
+//! as a signature of `data` alone or under another composite key.
+
+use std::marker::PhantomData;
+use arrayref::array_ref;
+use seckey::TempKey;
+use rand::{ Rng, CryptoRng };
+use crate::{ Packing, ByteArray, Concat, Error };
+use crate::seed;
+use super::{ Signature, DeterministicSignature, SeedableSignature, SEED_LENGTH };
+
+
+const DOMAIN: &[u8] = b"sarkara-composite";
+
+
+pub struct Composite<S1, S2>(PhantomData<(S1, S2)>);
+pub struct PrivateKey<S1: Signature, S2: Signature>(pub S1::PrivateKey, pub S2::PrivateKey, pub PublicKey<S1, S2>);
+pub struct PublicKey<S1: Signature, S2: Signature>(pub S1::PublicKey, pub S2::PublicKey);
+pub struct SignatureData<S1: Signature, S2: Signature>(pub S1::Signature, pub S2::Signature);
+
+impl<S1: Signature, S2: Signature> Signature for Composite<S1, S2> {
+    type PrivateKey = PrivateKey<S1, S2>;
+    type PublicKey = PublicKey<S1, S2>;
+    type Signature = SignatureData<S1, S2>;
+
+    const NAME: &'static str = "Composite";
+
+    fn keypair<R: Rng + CryptoRng>(mut r: R) -> (Self::PrivateKey, Self::PublicKey) {
+        let (sk1, pk1) = S1::keypair(&mut r);
+        let (sk2, pk2) = S2::keypair(&mut r);
+        let pk = PublicKey(pk1, pk2);
+        (PrivateKey(sk1, sk2, pk.clone()), pk)
+    }
+
+    fn signature<R: Rng + CryptoRng>(mut r: R, sk: &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+        let message = message(&sk.2, data);
+        SignatureData(
+            S1::signature(&mut r, &sk.0, &message),
+            S2::signature(&mut r, &sk.1, &message)
+        )
+    }
+
+    fn verify(pk: &Self::PublicKey, sig: &Self::Signature, data: &[u8]) -> Result<(), Error> {
+        let message = message(pk, data);
+        S1::verify(&pk.0, &sig.0, &message)?;
+        S2::verify(&pk.1, &sig.1, &message)
+    }
+}
+
+impl<S1, S2> SeedableSignature for Composite<S1, S2>
+    where
+        S1: SeedableSignature,
+        S2: SeedableSignature
+{
+    /// `seed` is expanded with SHAKE256 to one seed per component,
+    /// so each keeps its own seed format.
+    fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey) {
+        let mut seeds = [0; 2 * SEED_LENGTH];
+        let mut seeds = TempKey::from(&mut seeds);
+        seed::expand(seed, &mut seeds[..]);
+
+        let (sk1, pk1) = S1::keypair_from_seed(array_ref!(seeds, 0, SEED_LENGTH));
+        let (sk2, pk2) = S2::keypair_from_seed(array_ref!(seeds, SEED_LENGTH, SEED_LENGTH));
+        let pk = PublicKey(pk1, pk2);
+        (PrivateKey(sk1, sk2, pk.clone()), pk)
+    }
+}
+
+impl<S1, S2> DeterministicSignature for Composite<S1, S2>
+    where
+        S1: DeterministicSignature,
+        S2: DeterministicSignature
+{
+    fn signature(sk: &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+        let message = message(&sk.2, data);
+        SignatureData(
+            <S1 as DeterministicSignature>::signature(&sk.0, &message),
+            <S2 as DeterministicSignature>::signature(&sk.1, &message)
+        )
+    }
+}
+
+/// The composite prefix followed by `data`.
+fn message<S1: Signature, S2: Signature>(pk: &PublicKey<S1, S2>, data: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(
+        DOMAIN.len() + 2 + S1::NAME.len() + S2::NAME.len() + PublicKey::<S1, S2>::BYTES_LENGTH + data.len()
+    );
+    message.extend_from_slice(DOMAIN);
+    for name in &[S1::NAME, S2::NAME] {
+        message.push(name.len() as u8);
+        message.extend_from_slice(name.as_bytes());
+    }
+    pk.read_bytes(|buf| message.extend_from_slice(buf));
+    message.extend_from_slice(data);
+    message
+}
+
+impl<S1: Signature, S2: Signature> Clone for PublicKey<S1, S2> {
+    fn clone(&self) -> Self {
+        let mut bytes = <Self as Packing>::Bytes::zero();
+        self.read_bytes(|buf| bytes.as_mut().copy_from_slice(buf));
+        Packing::from_bytes(&bytes)
+    }
+}
+
+impl<S1: Signature, S2: Signature> Packing for PrivateKey<S1, S2> {
+    type Bytes = Concat<
+        Concat<<S1::PrivateKey as Packing>::Bytes, <S2::PrivateKey as Packing>::Bytes>,
+        <PublicKey<S1, S2> as Packing>::Bytes
+    >;
+
+    fn read_bytes<T, F>(&self, f: F)
+        -> T
+        where F: FnOnce(&[u8]) -> T
+    {
+        let mut bytes = Self::Bytes::zero();
+        let mut bytes = TempKey::from(&mut bytes);
+        self.0.read_bytes(|buf| (bytes.0).0.as_mut().copy_from_slice(buf));
+        self.1.read_bytes(|buf| (bytes.0).1.as_mut().copy_from_slice(buf));
+        self.2.read_bytes(|buf| bytes.1.as_mut().copy_from_slice(buf));
+        f((*bytes).as_ref())
+    }
+
+    fn from_bytes(buf: &Self::Bytes) -> Self {
+        PrivateKey(
+            Packing::from_bytes(&(buf.0).0),
+            Packing::from_bytes(&(buf.0).1),
+            Packing::from_bytes(&buf.1)
+        )
+    }
+}
+
+impl<S1: Signature, S2: Signature> PartialEq for PrivateKey<S1, S2>
+    where
+        S1::PrivateKey: PartialEq,
+        S2::PrivateKey: PartialEq,
+        S1::PublicKey: PartialEq,
+        S2::PublicKey: PartialEq
+{
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0 && self.1 == rhs.1 && self.2 == rhs.2
+    }
+}
+
+impl<S1: Signature, S2: Signature> Eq for PrivateKey<S1, S2>
+    where
+        S1::PrivateKey: Eq,
+        S2::PrivateKey: Eq,
+        S1::PublicKey: Eq,
+        S2::PublicKey: Eq
+{}
+
+macro_rules! composite {
+    ( $t:ident, $k:ident ) => {
+        impl<S1: Signature, S2: Signature> Packing for $t<S1, S2> {
+            type Bytes = Concat<
+                <S1::$k as Packing>::Bytes,
+                <S2::$k as Packing>::Bytes
+            >;
+
+            fn read_bytes<T, F>(&self, f: F)
+                -> T
+                where F: FnOnce(&[u8]) -> T
+            {
+                let mut bytes = Self::Bytes::zero();
+                let mut bytes = TempKey::from(&mut bytes);
+                self.0.read_bytes(|buf| bytes.0.as_mut().copy_from_slice(buf));
+                self.1.read_bytes(|buf| bytes.1.as_mut().copy_from_slice(buf));
+                f((*bytes).as_ref())
+            }
+
+            fn from_bytes(buf: &Self::Bytes) -> Self {
+                $t(Packing::from_bytes(&buf.0), Packing::from_bytes(&buf.1))
+            }
+        }
+
+        impl<S1: Signature, S2: Signature> PartialEq for $t<S1, S2>
+            where
+                S1::$k: PartialEq,
+                S2::$k: PartialEq
+        {
+            fn eq(&self, rhs: &Self) -> bool {
+                self.0 == rhs.0 && self.1 == rhs.1
+            }
+        }
+
+        impl<S1: Signature, S2: Signature> Eq for $t<S1, S2>
+            where
+                S1::$k: Eq,
+                S2::$k: Eq
+        {}
+    }
+}
+
+composite!(PublicKey, PublicKey);
+composite!(SignatureData, Signature);
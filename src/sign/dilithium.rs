@@ -1,7 +1,12 @@
+use arrayref::array_mut_ref;
+use seckey::TempKey;
 use rand::{ Rng, CryptoRng };
+use sha3::Shake256;
+use digest::{ Input, ExtendableOutput, XofReader };
 use dilithium::{ params, sign };
-use crate::{ Packing, Error };
-use super::{ Signature, DeterministicSignature };
+use crate::{ Packing, Error, SEED_LENGTH };
+use crate::seed::SeedRng;
+use super::{ Signature, DeterministicSignature, SeedableSignature };
 
 
 pub struct Dilithium;
@@ -14,6 +19,8 @@ impl Signature for Dilithium {
     type PublicKey = PublicKey;
     type Signature = SignatureData;
 
+    const NAME: &'static str = "Dilithium";
+
     fn keypair<R: Rng + CryptoRng>(mut r: R) -> (Self::PrivateKey, Self::PublicKey) {
         let mut sk = [0; params::SECRETKEYBYTES];
         let mut pk = [0; params::PUBLICKEYBYTES];
@@ -21,8 +28,24 @@ impl Signature for Dilithium {
         (PrivateKey(sk), PublicKey(pk))
     }
 
-    fn signature<R: Rng + CryptoRng>(_: R, sk: &Self::PrivateKey, data: &[u8]) -> Self::Signature {
-        <Dilithium as DeterministicSignature>::signature(sk, data)
+    /// Hedged, the key `K` that seeds the signing nonce is replaced by `SHAKE256(K || rnd)`.
+    ///
+    /// `K` is only used to derive the nonce, so the signature still verifies under the same public key.
+    fn signature<R: Rng + CryptoRng>(mut r: R, &PrivateKey(ref sk): &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+        let mut rnd = [0; params::SEEDBYTES];
+        r.fill_bytes(&mut rnd);
+
+        let mut sk = *sk;
+        let mut sk = TempKey::from(&mut sk);
+        let key = array_mut_ref!(sk, params::SEEDBYTES, params::SEEDBYTES);
+        let mut hasher = Shake256::default();
+        hasher.process(key);
+        hasher.process(&rnd);
+        hasher.xof_result().read(key);
+
+        let mut sig = [0; params::BYTES];
+        sign::sign(&mut sig, data, &sk);
+        SignatureData(sig)
     }
 
     fn verify(
@@ -46,6 +69,13 @@ impl DeterministicSignature for Dilithium {
     }
 }
 
+impl SeedableSignature for Dilithium {
+    /// `seed` is the key generation seed that Dilithium expands into `rho`, `rhoprime` and `K`.
+    fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey) {
+        Dilithium::keypair(SeedRng(seed))
+    }
+}
+
 eq!(PrivateKey);
 eq!(PublicKey);
 eq!(SignatureData);
@@ -0,0 +1,91 @@
+//! Falcon, with the padded signature encoding so `SignatureData` has a fixed length.
+//!
+//! NOTE pqcrypto-falcon draws its randomness from the OS,
+//! the `Rng` passed to `keypair` and `signature` is not used,
+//! so it is not a `SeedableSignature`.
+
+macro_rules! falcon {
+    ( $name:ident, $inner:ident, $alg:expr ; $pk:expr, $sk:expr, $sig:expr ) => {
+        pub mod $name {
+            use rand::{ Rng, CryptoRng };
+            use pqcrypto_falcon::$inner;
+            use pqcrypto_traits::sign::{
+                PublicKey as _, SecretKey as _,
+                DetachedSignature as _
+            };
+            use crate::{ Packing, Error };
+            use crate::sign::Signature;
+
+            pub const PUBLICKEYBYTES: usize = $pk;
+            pub const SECRETKEYBYTES: usize = $sk;
+            pub const BYTES: usize = $sig;
+
+            pub struct Falcon;
+            pub struct PrivateKey([u8; SECRETKEYBYTES]);
+            pub struct PublicKey([u8; PUBLICKEYBYTES]);
+            pub struct SignatureData([u8; BYTES]);
+
+            impl Signature for Falcon {
+                type PrivateKey = PrivateKey;
+                type PublicKey = PublicKey;
+                type Signature = SignatureData;
+
+                const NAME: &'static str = $alg;
+
+                fn keypair<R: Rng + CryptoRng>(_: R) -> (Self::PrivateKey, Self::PublicKey) {
+                    let (pk2, sk2) = $inner::keypair();
+                    let mut sk = [0; SECRETKEYBYTES];
+                    let mut pk = [0; PUBLICKEYBYTES];
+                    sk.copy_from_slice(sk2.as_bytes());
+                    pk.copy_from_slice(pk2.as_bytes());
+                    (PrivateKey(sk), PublicKey(pk))
+                }
+
+                fn signature<R: Rng + CryptoRng>(_: R, &PrivateKey(ref sk): &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+                    let sk = $inner::SecretKey::from_bytes(sk)
+                        .expect("the private key length is always correct");
+                    let mut sig = [0; BYTES];
+                    sig.copy_from_slice($inner::detached_sign(data, &sk).as_bytes());
+                    SignatureData(sig)
+                }
+
+                fn verify(
+                    &PublicKey(ref pk): &Self::PublicKey,
+                    &SignatureData(ref sig): &Self::Signature,
+                    data: &[u8]
+                ) -> Result<(), Error> {
+                    let pk = $inner::PublicKey::from_bytes(pk)
+                        .map_err(|_| Error::VerificationFailed)?;
+                    let sig = $inner::DetachedSignature::from_bytes(sig)
+                        .map_err(|_| Error::VerificationFailed)?;
+                    $inner::verify_detached_signature(&sig, data, &pk)
+                        .map_err(|_| Error::VerificationFailed)
+                }
+            }
+
+            eq!(PrivateKey);
+            eq!(PublicKey);
+            eq!(SignatureData);
+            packing!(PrivateKey; SECRETKEYBYTES);
+            packing!(PublicKey; PUBLICKEYBYTES);
+            packing!(SignatureData; BYTES);
+
+            #[cfg(feature = "serde")]
+            mod serde1 {
+                use std::fmt;
+                use serde::{
+                    Serialize, Serializer, Deserialize, Deserializer,
+                    de::{ self, Visitor }
+                };
+                use super::*;
+
+                serde!(PrivateKey);
+                serde!(PublicKey);
+                serde!(SignatureData);
+            }
+        }
+    }
+}
+
+falcon!(falcon512, falconpadded512, "Falcon-512"; 897, 1281, 666);
+falcon!(falcon1024, falconpadded1024, "Falcon-1024"; 1793, 2305, 1280);
@@ -0,0 +1,277 @@
+//! Leighton-Micali Signatures, RFC 8554.
+//!
+//! Only `LMS_SHA256_M32_H10` with `LMOTS_SHA256_N32_W4` is provided,
+//! a key can make 1024 signatures.
+//! The one-time private keys and `C` are derived from `SEED` as in Appendix A.
+//!
+//! Computing the Merkle tree takes all 1024 one-time public keys, so the private key
+//! keeps the tree (64 KiB) once `keypair` or the first `signature` after `from_bytes` has built it.
+
+use std::io;
+use rand::{ Rng, CryptoRng };
+use sha2::Sha256;
+use digest::{ Input, FixedOutput };
+use arrayref::{ array_ref, array_mut_ref };
+use crate::{ Packing, Error };
+use super::{ StatefulSignature, StateStore };
+
+
+const LMS_SHA256_M32_H10: u32 = 0x06;
+const LMOTS_SHA256_N32_W4: u32 = 0x03;
+
+const N: usize = 32;
+const W: usize = 4;
+const P: usize = 67;
+const LS: usize = 4;
+const H: usize = 10;
+const I_LENGTH: usize = 16;
+
+const D_PBLC: u16 = 0x8080;
+const D_MESG: u16 = 0x8181;
+const D_LEAF: u16 = 0x8282;
+const D_INTR: u16 = 0x8383;
+
+pub const MAX_SIGNATURES: u32 = 1 << H;
+pub const PRIVATEKEYBYTES: usize = 4 + I_LENGTH + N;
+pub const PUBLICKEYBYTES: usize = 4 + 4 + I_LENGTH + N;
+pub const BYTES: usize = 4 + (4 + N + P * N) + 4 + H * N;
+
+pub struct Lms;
+
+/// `q || I || SEED`, and the tree once it is built.
+pub struct PrivateKey([u8; PRIVATEKEYBYTES], Option<Vec<[u8; N]>>);
+
+/// `lms type || lmots type || I || T[1]`
+pub struct PublicKey([u8; PUBLICKEYBYTES]);
+
+/// `q || lmots type || C || y[0..p] || lms type || path[0..h]`
+pub struct SignatureData([u8; BYTES]);
+
+impl StatefulSignature for Lms {
+    type PrivateKey = PrivateKey;
+    type PublicKey = PublicKey;
+    type Signature = SignatureData;
+
+    fn keypair<R: Rng + CryptoRng>(mut r: R) -> (Self::PrivateKey, Self::PublicKey) {
+        let mut sk = [0; PRIVATEKEYBYTES];
+        r.fill_bytes(&mut sk[4..]);
+
+        let id = array_ref!(sk, 4, I_LENGTH);
+        let seed = array_ref!(sk, 4 + I_LENGTH, N);
+        let tree = tree(id, seed);
+
+        let mut pk = [0; PUBLICKEYBYTES];
+        pk[..4].copy_from_slice(&LMS_SHA256_M32_H10.to_be_bytes());
+        pk[4..8].copy_from_slice(&LMOTS_SHA256_N32_W4.to_be_bytes());
+        pk[8..][..I_LENGTH].copy_from_slice(id);
+        pk[8 + I_LENGTH..].copy_from_slice(&tree[1]);
+
+        (PrivateKey(sk, Some(tree)), PublicKey(pk))
+    }
+
+    fn remaining(&PrivateKey(ref sk, _): &Self::PrivateKey) -> u64 {
+        let q = u32::from_be_bytes(*array_ref!(sk, 0, 4));
+        u64::from(MAX_SIGNATURES.saturating_sub(q))
+    }
+
+    fn signature<S: StateStore>(store: &mut S, &mut PrivateKey(ref mut sk, ref mut tree): &mut Self::PrivateKey, data: &[u8])
+        -> io::Result<Self::Signature>
+    {
+        let q = u32::from_be_bytes(*array_ref!(sk, 0, 4));
+        if q >= MAX_SIGNATURES {
+            return Err(Error::KeyExhausted.into());
+        }
+
+        // the in-memory key stays advanced even if `store` fails
+        array_mut_ref!(sk, 0, 4).copy_from_slice(&(q + 1).to_be_bytes());
+        store.store(&sk[..])?;
+
+        let id = array_ref!(sk, 4, I_LENGTH);
+        let seed = array_ref!(sk, 4 + I_LENGTH, N);
+
+        let mut sig = [0; BYTES];
+        let (ots, path) = sig.split_at_mut(4 + 4 + N + P * N);
+        ots[..4].copy_from_slice(&q.to_be_bytes());
+        ots[4..8].copy_from_slice(&LMOTS_SHA256_N32_W4.to_be_bytes());
+
+        let c = hash(&[id, &q.to_be_bytes(), &0xfffd_u16.to_be_bytes(), &[0xff], seed]);
+        let digits = digits(&hash(&[id, &q.to_be_bytes(), &D_MESG.to_be_bytes(), &c, data]));
+        ots[8..][..N].copy_from_slice(&c);
+        for (i, y) in ots[8 + N..].chunks_mut(N).enumerate() {
+            let x = ots_private(id, seed, q, i);
+            y.copy_from_slice(&chain(id, q, i, 0, digits[i], x));
+        }
+
+        let tree = tree.get_or_insert_with(|| self::tree(id, seed));
+        path[..4].copy_from_slice(&LMS_SHA256_M32_H10.to_be_bytes());
+        let mut node = (1 << H) + q as usize;
+        for p in path[4..].chunks_mut(N) {
+            p.copy_from_slice(&tree[node ^ 1]);
+            node >>= 1;
+        }
+
+        Ok(SignatureData(sig))
+    }
+
+    fn verify(
+        &PublicKey(ref pk): &Self::PublicKey,
+        &SignatureData(ref sig): &Self::Signature,
+        data: &[u8]
+    ) -> Result<(), Error> {
+        let id = array_ref!(pk, 8, I_LENGTH);
+        let root = array_ref!(pk, 8 + I_LENGTH, N);
+        let q = u32::from_be_bytes(*array_ref!(sig, 0, 4));
+        let (ots, path) = sig.split_at(4 + 4 + N + P * N);
+
+        if pk[..4] != LMS_SHA256_M32_H10.to_be_bytes()
+            || pk[4..8] != LMOTS_SHA256_N32_W4.to_be_bytes()
+            || ots[4..8] != LMOTS_SHA256_N32_W4.to_be_bytes()
+            || path[..4] != LMS_SHA256_M32_H10.to_be_bytes()
+            || q >= MAX_SIGNATURES
+        {
+            return Err(Error::VerificationFailed);
+        }
+
+        let c = &ots[8..][..N];
+        let digits = digits(&hash(&[id, &q.to_be_bytes(), &D_MESG.to_be_bytes(), c, data]));
+
+        let mut hasher = Sha256::default();
+        hasher.process(id);
+        hasher.process(&q.to_be_bytes());
+        hasher.process(&D_PBLC.to_be_bytes());
+        for (i, y) in ots[8 + N..].chunks(N).enumerate() {
+            hasher.process(&chain(id, q, i, digits[i], (1 << W) - 1, *array_ref!(y, 0, N)));
+        }
+        let mut k = [0; N];
+        k.copy_from_slice(&hasher.fixed_result());
+
+        let mut node = (1 << H) + q;
+        let mut tmp = hash(&[id, &node.to_be_bytes(), &D_LEAF.to_be_bytes(), &k]);
+        for p in path[4..].chunks(N) {
+            tmp = if node & 1 == 1 {
+                hash(&[id, &(node / 2).to_be_bytes(), &D_INTR.to_be_bytes(), p, &tmp])
+            } else {
+                hash(&[id, &(node / 2).to_be_bytes(), &D_INTR.to_be_bytes(), &tmp, p])
+            };
+            node /= 2;
+        }
+
+        if &tmp == root {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+fn hash(parts: &[&[u8]]) -> [u8; N] {
+    let mut hasher = Sha256::default();
+    for part in parts {
+        hasher.process(part);
+    }
+
+    let mut output = [0; N];
+    output.copy_from_slice(&hasher.fixed_result());
+    output
+}
+
+fn coef(s: &[u8], i: usize) -> u8 {
+    let shift = 8 - (W * (i % (8 / W)) + W);
+    (s[i * W / 8] >> shift) & ((1 << W) - 1)
+}
+
+/// `Q || Cksm(Q)` as base `2^W` digits.
+fn digits(q: &[u8; N]) -> [u8; P] {
+    const U: usize = N * 8 / W;
+
+    let sum = (0..U)
+        .map(|i| u16::from((1 << W) - 1 - coef(q, i)))
+        .sum::<u16>();
+    let cksm = (sum << LS).to_be_bytes();
+
+    let mut digits = [0; P];
+    for (i, d) in digits.iter_mut().enumerate() {
+        *d = if i < U { coef(q, i) } else { coef(&cksm, i - U) };
+    }
+    digits
+}
+
+fn chain(id: &[u8; I_LENGTH], q: u32, i: usize, start: u8, end: u8, mut tmp: [u8; N]) -> [u8; N] {
+    for j in start..end {
+        tmp = hash(&[id, &q.to_be_bytes(), &(i as u16).to_be_bytes(), &[j], &tmp]);
+    }
+    tmp
+}
+
+fn ots_private(id: &[u8; I_LENGTH], seed: &[u8; N], q: u32, i: usize) -> [u8; N] {
+    hash(&[id, &q.to_be_bytes(), &(i as u16).to_be_bytes(), &[0xff], seed])
+}
+
+fn ots_public(id: &[u8; I_LENGTH], seed: &[u8; N], q: u32) -> [u8; N] {
+    let mut hasher = Sha256::default();
+    hasher.process(id);
+    hasher.process(&q.to_be_bytes());
+    hasher.process(&D_PBLC.to_be_bytes());
+    for i in 0..P {
+        let x = ots_private(id, seed, q, i);
+        hasher.process(&chain(id, q, i, 0, (1 << W) - 1, x));
+    }
+
+    let mut output = [0; N];
+    output.copy_from_slice(&hasher.fixed_result());
+    output
+}
+
+/// `T[1..2^(H+1)]`, `T[0]` is unused.
+fn tree(id: &[u8; I_LENGTH], seed: &[u8; N]) -> Vec<[u8; N]> {
+    let mut tree = vec![[0; N]; 2 << H];
+
+    for q in 0..MAX_SIGNATURES {
+        let r = (1 << H) + q;
+        let k = ots_public(id, seed, q);
+        tree[r as usize] = hash(&[id, &r.to_be_bytes(), &D_LEAF.to_be_bytes(), &k]);
+    }
+
+    for r in (1..(1 << H) as u32).rev() {
+        let (left, right) = (tree[2 * r as usize], tree[2 * r as usize + 1]);
+        tree[r as usize] = hash(&[id, &r.to_be_bytes(), &D_INTR.to_be_bytes(), &left, &right]);
+    }
+
+    tree
+}
+
+eq!(PrivateKey);
+eq!(PublicKey);
+eq!(SignatureData);
+packing!(PublicKey; PUBLICKEYBYTES);
+packing!(SignatureData; BYTES);
+
+impl Packing for PrivateKey {
+    type Bytes = [u8; PRIVATEKEYBYTES];
+
+    fn read_bytes<T, F>(&self, f: F)
+        -> T
+        where F: FnOnce(&[u8]) -> T
+    {
+        f(&self.0)
+    }
+
+    /// The tree is built again by the next `signature`.
+    fn from_bytes(buf: &Self::Bytes) -> Self {
+        PrivateKey(*buf, None)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde1 {
+    use std::fmt;
+    use serde::{
+        Serialize, Serializer, Deserialize, Deserializer,
+        de::{ self, Visitor }
+    };
+    use super::*;
+
+    serde!(PrivateKey);
+    serde!(PublicKey);
+    serde!(SignatureData);
+}
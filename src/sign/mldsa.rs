@@ -0,0 +1,226 @@
+//! ML-DSA, FIPS 204.
+//!
+//! `Signature::signature` is the hedged mode and `DeterministicSignature::signature`
+//! the deterministic mode (`rnd` is all zero).
+//! `signature` and `verify` are ML-DSA.Sign and ML-DSA.Verify with an empty context,
+//! `signature_with_context` takes the context, and `signature_prehashed` is HashML-DSA with SHAKE256.
+
+macro_rules! mldsa {
+    ( $name:ident, $params:ident, $set:expr ; $pk:expr, $sk:expr, $sig:expr ) => {
+        pub mod $name {
+            use std::convert::TryFrom;
+            use rand::{ Rng, CryptoRng };
+            use ml_dsa::{
+                $params, KeyGen, SigningKey, VerifyingKey,
+                EncodedSigningKey, EncodedVerifyingKey, EncodedSignature
+            };
+            use crate::{ Packing, Error };
+            use crate::sign::{ Signature, DeterministicSignature, SeedableSignature, SEED_LENGTH, prehash };
+            use crate::sign::prehash::PREHASH_LENGTH;
+
+            pub const PUBLICKEYBYTES: usize = $pk;
+            pub const SECRETKEYBYTES: usize = $sk;
+            pub const BYTES: usize = $sig;
+
+            pub struct MlDsa;
+            pub struct PrivateKey([u8; SECRETKEYBYTES]);
+            pub struct PublicKey([u8; PUBLICKEYBYTES]);
+            pub struct SignatureData([u8; BYTES]);
+
+            impl MlDsa {
+                /// `message` is the FIPS 204 internal message `M'`.
+                fn sign(sk: &[u8; SECRETKEYBYTES], message: &[&[u8]], rnd: [u8; 32]) -> SignatureData {
+                    let sk = EncodedSigningKey::<$params>::try_from(&sk[..])
+                        .expect("the private key length is always correct");
+                    let sk = SigningKey::<$params>::decode(&sk);
+                    let mut sig = [0; BYTES];
+                    sig.copy_from_slice(&sk.sign_internal(message, &rnd.into()).encode());
+                    SignatureData(sig)
+                }
+
+                fn verify(pk: &[u8; PUBLICKEYBYTES], sig: &[u8; BYTES], message: &[&[u8]]) -> Result<(), Error> {
+                    let pk = EncodedVerifyingKey::<$params>::try_from(&pk[..])
+                        .map_err(|_| Error::VerificationFailed)?;
+                    let pk = VerifyingKey::<$params>::decode(&pk);
+                    let sig = EncodedSignature::<$params>::try_from(&sig[..])
+                        .ok()
+                        .and_then(|sig| ml_dsa::Signature::<$params>::decode(&sig))
+                        .ok_or(Error::VerificationFailed)?;
+
+                    if pk.verify_internal(message, &sig) {
+                        Ok(())
+                    } else {
+                        Err(Error::VerificationFailed)
+                    }
+                }
+            }
+
+            impl Signature for MlDsa {
+                type PrivateKey = PrivateKey;
+                type PublicKey = PublicKey;
+                type Signature = SignatureData;
+
+                const NAME: &'static str = $set;
+
+                fn keypair<R: Rng + CryptoRng>(mut r: R) -> (Self::PrivateKey, Self::PublicKey) {
+                    let mut xi = [0; SEED_LENGTH];
+                    r.fill_bytes(&mut xi);
+                    MlDsa::keypair_from_seed(&xi)
+                }
+
+                fn signature<R: Rng + CryptoRng>(mut r: R, &PrivateKey(ref sk): &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+                    let mut rnd = [0; 32];
+                    r.fill_bytes(&mut rnd);
+                    MlDsa::sign(sk, &[&[0x00, 0x00], data], rnd)
+                }
+
+                fn verify(
+                    &PublicKey(ref pk): &Self::PublicKey,
+                    &SignatureData(ref sig): &Self::Signature,
+                    data: &[u8]
+                ) -> Result<(), Error> {
+                    MlDsa::verify(pk, sig, &[&[0x00, 0x00], data])
+                }
+
+                // same encoding as the provided methods, without another prefix
+
+                fn signature_with_context<R: Rng + CryptoRng>(mut r: R, &PrivateKey(ref sk): &Self::PrivateKey, ctx: &[u8], data: &[u8])
+                    -> Result<Self::Signature, Error>
+                {
+                    if ctx.len() > 255 {
+                        return Err(Error::Length);
+                    }
+
+                    let mut rnd = [0; 32];
+                    r.fill_bytes(&mut rnd);
+                    Ok(MlDsa::sign(sk, &[&[0x00, ctx.len() as u8], ctx, data], rnd))
+                }
+
+                fn verify_with_context(
+                    &PublicKey(ref pk): &Self::PublicKey,
+                    &SignatureData(ref sig): &Self::Signature,
+                    ctx: &[u8],
+                    data: &[u8]
+                ) -> Result<(), Error> {
+                    if ctx.len() > 255 {
+                        return Err(Error::Length);
+                    }
+
+                    MlDsa::verify(pk, sig, &[&[0x00, ctx.len() as u8], ctx, data])
+                }
+
+                fn signature_prehashed<R: Rng + CryptoRng>(mut r: R, &PrivateKey(ref sk): &Self::PrivateKey, ctx: &[u8], digest: &[u8; PREHASH_LENGTH])
+                    -> Result<Self::Signature, Error>
+                {
+                    let message = prehash::encode(ctx, digest)?;
+                    let mut rnd = [0; 32];
+                    r.fill_bytes(&mut rnd);
+                    Ok(MlDsa::sign(sk, &[&message], rnd))
+                }
+
+                fn verify_prehashed(
+                    &PublicKey(ref pk): &Self::PublicKey,
+                    &SignatureData(ref sig): &Self::Signature,
+                    ctx: &[u8],
+                    digest: &[u8; PREHASH_LENGTH]
+                ) -> Result<(), Error> {
+                    let message = prehash::encode(ctx, digest)?;
+                    MlDsa::verify(pk, sig, &[&message])
+                }
+            }
+
+            impl SeedableSignature for MlDsa {
+                /// `seed` is the FIPS 204 key generation seed `ξ`.
+                fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey) {
+                    let keypair = $params::key_gen_internal(&(*seed).into());
+                    let mut sk = [0; SECRETKEYBYTES];
+                    let mut pk = [0; PUBLICKEYBYTES];
+                    sk.copy_from_slice(&keypair.signing_key().encode());
+                    pk.copy_from_slice(&keypair.verifying_key().encode());
+                    (PrivateKey(sk), PublicKey(pk))
+                }
+            }
+
+            impl DeterministicSignature for MlDsa {
+                fn signature(&PrivateKey(ref sk): &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+                    MlDsa::sign(sk, &[&[0x00, 0x00], data], [0; 32])
+                }
+
+                fn signature_with_context(&PrivateKey(ref sk): &Self::PrivateKey, ctx: &[u8], data: &[u8])
+                    -> Result<Self::Signature, Error>
+                {
+                    if ctx.len() > 255 {
+                        return Err(Error::Length);
+                    }
+
+                    Ok(MlDsa::sign(sk, &[&[0x00, ctx.len() as u8], ctx, data], [0; 32]))
+                }
+            }
+
+            eq!(PrivateKey);
+            eq!(PublicKey);
+            eq!(SignatureData);
+            packing!(PrivateKey; SECRETKEYBYTES);
+            packing!(PublicKey; PUBLICKEYBYTES);
+            packing!(SignatureData; BYTES);
+
+            #[cfg(feature = "serde")]
+            mod serde1 {
+                use std::fmt;
+                use serde::{
+                    Serialize, Serializer, Deserialize, Deserializer,
+                    de::{ self, Visitor }
+                };
+                use super::*;
+
+                serde!(PrivateKey);
+                serde!(PublicKey);
+                serde!(SignatureData);
+            }
+
+            /// The ACVP messages are the internal message `M'`.
+            #[cfg(test)]
+            mod tests {
+                use std::convert::TryFrom;
+                use crate::Packing;
+                use crate::sign::SeedableSignature;
+                use crate::acvp::{ vectors, hex };
+                use super::*;
+
+                #[test]
+                fn test_acvp_keygen() {
+                    for v in vectors(include_str!("../../tests/acvp/mldsa_keygen.txt"), $set) {
+                        let seed = <[u8; SEED_LENGTH]>::try_from(&hex(v["seed"])[..]).unwrap();
+                        let (sk, pk) = MlDsa::keypair_from_seed(&seed);
+                        assert!(sk.read_bytes(|sk| sk == &hex(v["sk"])[..]), "tcId {}", v["tcId"]);
+                        assert!(pk.read_bytes(|pk| pk == &hex(v["pk"])[..]), "tcId {}", v["tcId"]);
+                    }
+                }
+
+                #[test]
+                fn test_acvp_siggen() {
+                    for v in vectors(include_str!("../../tests/acvp/mldsa_siggen.txt"), $set) {
+                        let sk = <[u8; SECRETKEYBYTES]>::try_from(&hex(v["sk"])[..]).unwrap();
+                        let rnd = <[u8; 32]>::try_from(&hex(v["rnd"])[..]).unwrap();
+                        let SignatureData(sig) = MlDsa::sign(&sk, &[&hex(v["message"])], rnd);
+                        assert_eq!(&sig[..], &hex(v["signature"])[..], "tcId {}", v["tcId"]);
+                    }
+                }
+
+                #[test]
+                fn test_acvp_sigver() {
+                    for v in vectors(include_str!("../../tests/acvp/mldsa_sigver.txt"), $set) {
+                        let pk = <[u8; PUBLICKEYBYTES]>::try_from(&hex(v["pk"])[..]).unwrap();
+                        let sig = <[u8; BYTES]>::try_from(&hex(v["signature"])[..]).unwrap();
+                        let result = MlDsa::verify(&pk, &sig, &[&hex(v["message"])]);
+                        assert_eq!(result.is_ok(), v["testPassed"] == "true", "tcId {}", v["tcId"]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+mldsa!(mldsa44, MlDsa44, "ML-DSA-44"; 1312, 2560, 2420);
+mldsa!(mldsa65, MlDsa65, "ML-DSA-65"; 1952, 4032, 3309);
+mldsa!(mldsa87, MlDsa87, "ML-DSA-87"; 2592, 4896, 4627);
@@ -1,7 +1,19 @@
+use std::{ cmp, thread };
+use std::io::{ self, Read };
 use rand::{ Rng, CryptoRng };
 use crate::{ Packing, Error };
+use self::prehash::PREHASH_LENGTH;
 
+pub mod composite;
 pub mod dilithium;
+pub mod falcon;
+pub mod lms;
+pub mod mldsa;
+pub mod prehash;
+pub mod sphincs;
+
+pub use self::composite::Composite;
+pub use crate::SEED_LENGTH;
 
 
 pub trait Signature {
@@ -9,13 +21,147 @@ pub trait Signature {
     type PublicKey: Packing;
     type Signature: Packing;
 
+    /// The scheme and its parameter set, such as `ML-DSA-65`.
+    const NAME: &'static str;
+
     fn keypair<R: Rng + CryptoRng>(r: R) -> (Self::PrivateKey, Self::PublicKey);
 
     fn signature<R: Rng + CryptoRng>(r: R, sk: &Self::PrivateKey, data: &[u8]) -> Self::Signature;
 
     fn verify(pk: &Self::PublicKey, sig: &Self::Signature, data: &[u8]) -> Result<(), Error>;
+
+    /// Signs `0x00 || len(ctx) || ctx || data` as in FIPS 204,
+    /// `ctx` separates the kinds of objects signed with one key and is at most 255 bytes.
+    ///
+    /// NOTE `signature` signs `data` as is, so it is not separated from this,
+    /// except for ML-DSA and SLH-DSA where `signature` is the empty `ctx`.
+    fn signature_with_context<R: Rng + CryptoRng>(r: R, sk: &Self::PrivateKey, ctx: &[u8], data: &[u8])
+        -> Result<Self::Signature, Error>
+    {
+        let message = encode(0x00, ctx, &[data])?;
+        Ok(Self::signature(r, sk, &message))
+    }
+
+    fn verify_with_context(pk: &Self::PublicKey, sig: &Self::Signature, ctx: &[u8], data: &[u8]) -> Result<(), Error> {
+        let message = encode(0x00, ctx, &[data])?;
+        Self::verify(pk, sig, &message)
+    }
+
+    /// `digest` is `prehash::prehash` of the message.
+    fn signature_prehashed<R: Rng + CryptoRng>(r: R, sk: &Self::PrivateKey, ctx: &[u8], digest: &[u8; PREHASH_LENGTH])
+        -> Result<Self::Signature, Error>
+    {
+        let message = prehash::encode(ctx, digest)?;
+        Ok(Self::signature(r, sk, &message))
+    }
+
+    fn verify_prehashed(pk: &Self::PublicKey, sig: &Self::Signature, ctx: &[u8], digest: &[u8; PREHASH_LENGTH])
+        -> Result<(), Error>
+    {
+        let message = prehash::encode(ctx, digest)?;
+        Self::verify(pk, sig, &message)
+    }
+
+    /// Same as `signature_prehashed`, without holding the message in memory.
+    fn signature_reader<R: Rng + CryptoRng, I: Read>(r: R, sk: &Self::PrivateKey, ctx: &[u8], input: I)
+        -> io::Result<Self::Signature>
+    {
+        let digest = prehash::prehash_reader(input)?;
+        Ok(Self::signature_prehashed(r, sk, ctx, &digest)?)
+    }
+
+    /// A signature that does not verify is `io::ErrorKind::InvalidData`.
+    fn verify_reader<I: Read>(pk: &Self::PublicKey, sig: &Self::Signature, ctx: &[u8], input: I) -> io::Result<()> {
+        let digest = prehash::prehash_reader(input)?;
+        Self::verify_prehashed(pk, sig, ctx, &digest)?;
+        Ok(())
+    }
+
+    /// Verifies `(pk, sig, data)` items on all available threads,
+    /// the results are in the same order as `items`.
+    fn verify_batch(items: &[(&Self::PublicKey, &Self::Signature, &[u8])]) -> Vec<Result<(), Error>>
+        where
+            Self::PublicKey: Sync,
+            Self::Signature: Sync
+    {
+        let threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        let chunk = cmp::max(items.len().div_ceil(threads), 1);
+
+        thread::scope(|scope| {
+            let handles = items.chunks(chunk)
+                .map(|items| scope.spawn(move || items.iter()
+                    .map(|&(pk, sig, data)| Self::verify(pk, sig, data))
+                    .collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>();
+
+            handles.into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
 }
 
 pub trait DeterministicSignature: Signature {
     fn signature(sk: &Self::PrivateKey, data: &[u8]) -> Self::Signature;
+
+    fn signature_with_context(sk: &Self::PrivateKey, ctx: &[u8], data: &[u8]) -> Result<Self::Signature, Error> {
+        let message = encode(0x00, ctx, &[data])?;
+        Ok(<Self as DeterministicSignature>::signature(sk, &message))
+    }
+}
+
+/// Signature schemes whose key generation can be driven by a seed.
+pub trait SeedableSignature: Signature {
+    /// The same `seed` always gives the same keypair.
+    fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey);
+}
+
+/// Durable storage for the state of a `StatefulSignature` private key.
+pub trait StateStore {
+    /// Must not return `Ok` before `state` is durable,
+    /// such as after `File::sync_data`.
+    fn store(&mut self, state: &[u8]) -> io::Result<()>;
+}
+
+/// Signature schemes whose private key must never be used twice in the same state.
+///
+/// The advanced private key is written to a `StateStore` before the signature is computed,
+/// so a crash can only skip a one-time key, never reuse one.
+/// Restoring an older state with `Packing::from_bytes` breaks this.
+pub trait StatefulSignature {
+    type PrivateKey: Packing;
+    type PublicKey: Packing;
+    type Signature: Packing;
+
+    fn keypair<R: Rng + CryptoRng>(r: R) -> (Self::PrivateKey, Self::PublicKey);
+
+    /// Number of signatures the private key can still make.
+    fn remaining(sk: &Self::PrivateKey) -> u64;
+
+    /// An exhausted key is `Error::KeyExhausted`, errors of `store` are returned as is.
+    fn signature<S: StateStore>(store: &mut S, sk: &mut Self::PrivateKey, data: &[u8]) -> io::Result<Self::Signature>;
+
+    fn verify(pk: &Self::PublicKey, sig: &Self::Signature, data: &[u8]) -> Result<(), Error>;
+}
+
+
+/// `domain || len(ctx) || ctx || parts..`
+fn encode(domain: u8, ctx: &[u8], parts: &[&[u8]]) -> Result<Vec<u8>, Error> {
+    if ctx.len() > 255 {
+        return Err(Error::Length);
+    }
+
+    let len = parts.iter().map(|part| part.len()).sum::<usize>();
+    let mut message = Vec::with_capacity(2 + ctx.len() + len);
+    message.push(domain);
+    message.push(ctx.len() as u8);
+    message.extend_from_slice(ctx);
+    for part in parts {
+        message.extend_from_slice(part);
+    }
+
+    Ok(message)
 }
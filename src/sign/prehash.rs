@@ -0,0 +1,50 @@
+//! Pre-hashing for `Signature::signature_prehashed`.
+//!
+//! The message is hashed with SHAKE256 and the signed message is
+//! `0x01 || len(ctx) || ctx || OID(SHAKE256) || PH(M)`, following HashML-DSA in FIPS 204,
+//! so it can not collide with a pre-hash under another hash function.
+
+use std::io::{ self, Read };
+use sha3::Shake256;
+use digest::{ Input, ExtendableOutput, XofReader };
+use crate::Error;
+
+
+pub const PREHASH_LENGTH: usize = 64;
+
+/// DER encoding of `2.16.840.1.101.3.4.2.12`.
+const SHAKE256_OID: [u8; 11] = [0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x0c];
+
+const CHUNK_LENGTH: usize = 8 * 1024;
+
+pub fn prehash(data: &[u8]) -> [u8; PREHASH_LENGTH] {
+    let mut hasher = Shake256::default();
+    hasher.process(data);
+    finish(hasher)
+}
+
+pub fn prehash_reader<R: Read>(mut reader: R) -> io::Result<[u8; PREHASH_LENGTH]> {
+    let mut hasher = Shake256::default();
+    let mut buf = vec![0; CHUNK_LENGTH];
+
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => hasher.process(&buf[..n]),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err)
+        }
+    }
+
+    Ok(finish(hasher))
+}
+
+fn finish(hasher: Shake256) -> [u8; PREHASH_LENGTH] {
+    let mut output = [0; PREHASH_LENGTH];
+    hasher.xof_result().read(&mut output);
+    output
+}
+
+pub(crate) fn encode(ctx: &[u8], digest: &[u8; PREHASH_LENGTH]) -> Result<Vec<u8>, Error> {
+    super::encode(0x01, ctx, &[&SHAKE256_OID, digest])
+}
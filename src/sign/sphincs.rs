@@ -0,0 +1,193 @@
+//! SPHINCS+ as standardized in FIPS 205 (SLH-DSA).
+//!
+//! Only the SHAKE parameter sets are provided.
+//! `s` sets have smaller signatures, `f` sets sign faster.
+//!
+//! `signature` and `verify` are SLH-DSA.Sign and SLH-DSA.Verify with an empty context,
+//! `signature_with_context` takes the context, and `signature_prehashed` is HashSLH-DSA with SHAKE256.
+
+macro_rules! sphincs {
+    ( $name:ident, $params:ident, $alg:expr ; $n:expr, $sig:expr ) => {
+        pub mod $name {
+            use std::convert::TryFrom;
+            use rand::{ Rng, CryptoRng };
+            use slh_dsa::{ $params, SigningKey, VerifyingKey };
+            use seckey::TempKey;
+            use crate::{ Packing, Error };
+            use crate::sign::{ Signature, DeterministicSignature, SeedableSignature, SEED_LENGTH, prehash };
+            use crate::sign::prehash::PREHASH_LENGTH;
+
+            pub const N: usize = $n;
+            pub const PRIVATEKEYBYTES: usize = 4 * N;
+            pub const PUBLICKEYBYTES: usize = 2 * N;
+            pub const BYTES: usize = $sig;
+
+            pub struct Sphincs;
+            pub struct PrivateKey([u8; PRIVATEKEYBYTES]);
+            pub struct PublicKey([u8; PUBLICKEYBYTES]);
+            pub struct SignatureData([u8; BYTES]);
+
+            impl Sphincs {
+                /// `seed` is `SK.seed || SK.prf || PK.seed`.
+                fn keygen(seed: &[u8; 3 * N]) -> (PrivateKey, PublicKey) {
+                    let (sk_seed, seed) = seed.split_at(N);
+                    let (sk_prf, pk_seed) = seed.split_at(N);
+
+                    let key = SigningKey::<$params>::slh_keygen_internal(sk_seed, sk_prf, pk_seed);
+                    let mut sk = [0; PRIVATEKEYBYTES];
+                    let mut pk = [0; PUBLICKEYBYTES];
+                    sk.copy_from_slice(&key.to_bytes());
+                    pk.copy_from_slice(&key.verifying_key().to_bytes());
+                    (PrivateKey(sk), PublicKey(pk))
+                }
+
+                /// `message` is the FIPS 205 internal message `M'`.
+                fn sign(sk: &[u8; PRIVATEKEYBYTES], message: &[&[u8]], opt_rand: Option<&[u8]>) -> SignatureData {
+                    let sk = SigningKey::<$params>::try_from(&sk[..])
+                        .expect("the private key length is always correct");
+                    let mut sig = [0; BYTES];
+                    sig.copy_from_slice(&sk.slh_sign_internal(message, opt_rand).to_bytes());
+                    SignatureData(sig)
+                }
+
+                fn verify(pk: &[u8; PUBLICKEYBYTES], sig: &[u8; BYTES], message: &[&[u8]]) -> Result<(), Error> {
+                    let pk = VerifyingKey::<$params>::try_from(&pk[..])
+                        .map_err(|_| Error::VerificationFailed)?;
+                    let sig = slh_dsa::Signature::<$params>::try_from(&sig[..])
+                        .map_err(|_| Error::VerificationFailed)?;
+                    pk.slh_verify_internal(message, &sig)
+                        .map_err(|_| Error::VerificationFailed)
+                }
+            }
+
+            impl Signature for Sphincs {
+                type PrivateKey = PrivateKey;
+                type PublicKey = PublicKey;
+                type Signature = SignatureData;
+
+                const NAME: &'static str = $alg;
+
+                fn keypair<R: Rng + CryptoRng>(mut r: R) -> (Self::PrivateKey, Self::PublicKey) {
+                    let mut seed = [0; 3 * N];
+                    r.fill_bytes(&mut seed);
+                    Sphincs::keygen(&seed)
+                }
+
+                /// Hedged, mixes `N` bytes from `r` into the signature randomness.
+                fn signature<R: Rng + CryptoRng>(mut r: R, &PrivateKey(ref sk): &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+                    let mut opt_rand = [0; N];
+                    r.fill_bytes(&mut opt_rand);
+                    Sphincs::sign(sk, &[&[0x00, 0x00], data], Some(&opt_rand))
+                }
+
+                fn verify(
+                    &PublicKey(ref pk): &Self::PublicKey,
+                    &SignatureData(ref sig): &Self::Signature,
+                    data: &[u8]
+                ) -> Result<(), Error> {
+                    Sphincs::verify(pk, sig, &[&[0x00, 0x00], data])
+                }
+
+                // same encoding as the provided methods, without another prefix
+
+                fn signature_with_context<R: Rng + CryptoRng>(mut r: R, &PrivateKey(ref sk): &Self::PrivateKey, ctx: &[u8], data: &[u8])
+                    -> Result<Self::Signature, Error>
+                {
+                    if ctx.len() > 255 {
+                        return Err(Error::Length);
+                    }
+
+                    let mut opt_rand = [0; N];
+                    r.fill_bytes(&mut opt_rand);
+                    Ok(Sphincs::sign(sk, &[&[0x00, ctx.len() as u8], ctx, data], Some(&opt_rand)))
+                }
+
+                fn verify_with_context(
+                    &PublicKey(ref pk): &Self::PublicKey,
+                    &SignatureData(ref sig): &Self::Signature,
+                    ctx: &[u8],
+                    data: &[u8]
+                ) -> Result<(), Error> {
+                    if ctx.len() > 255 {
+                        return Err(Error::Length);
+                    }
+
+                    Sphincs::verify(pk, sig, &[&[0x00, ctx.len() as u8], ctx, data])
+                }
+
+                fn signature_prehashed<R: Rng + CryptoRng>(mut r: R, &PrivateKey(ref sk): &Self::PrivateKey, ctx: &[u8], digest: &[u8; PREHASH_LENGTH])
+                    -> Result<Self::Signature, Error>
+                {
+                    let message = prehash::encode(ctx, digest)?;
+                    let mut opt_rand = [0; N];
+                    r.fill_bytes(&mut opt_rand);
+                    Ok(Sphincs::sign(sk, &[&message], Some(&opt_rand)))
+                }
+
+                fn verify_prehashed(
+                    &PublicKey(ref pk): &Self::PublicKey,
+                    &SignatureData(ref sig): &Self::Signature,
+                    ctx: &[u8],
+                    digest: &[u8; PREHASH_LENGTH]
+                ) -> Result<(), Error> {
+                    let message = prehash::encode(ctx, digest)?;
+                    Sphincs::verify(pk, sig, &[&message])
+                }
+            }
+
+            impl SeedableSignature for Sphincs {
+                /// `seed` is expanded with SHAKE256 to `SK.seed || SK.prf || PK.seed`.
+                fn keypair_from_seed(seed: &[u8; SEED_LENGTH]) -> (Self::PrivateKey, Self::PublicKey) {
+                    let mut seeds = [0; 3 * N];
+                    let mut seeds = TempKey::from(&mut seeds);
+                    crate::seed::expand(seed, &mut seeds[..]);
+                    Sphincs::keygen(&seeds)
+                }
+            }
+
+            impl DeterministicSignature for Sphincs {
+                fn signature(&PrivateKey(ref sk): &Self::PrivateKey, data: &[u8]) -> Self::Signature {
+                    Sphincs::sign(sk, &[&[0x00, 0x00], data], None)
+                }
+
+                fn signature_with_context(&PrivateKey(ref sk): &Self::PrivateKey, ctx: &[u8], data: &[u8])
+                    -> Result<Self::Signature, Error>
+                {
+                    if ctx.len() > 255 {
+                        return Err(Error::Length);
+                    }
+
+                    Ok(Sphincs::sign(sk, &[&[0x00, ctx.len() as u8], ctx, data], None))
+                }
+            }
+
+            eq!(PrivateKey);
+            eq!(PublicKey);
+            eq!(SignatureData);
+            packing!(PrivateKey; PRIVATEKEYBYTES);
+            packing!(PublicKey; PUBLICKEYBYTES);
+            packing!(SignatureData; BYTES);
+
+            #[cfg(feature = "serde")]
+            mod serde1 {
+                use std::fmt;
+                use serde::{
+                    Serialize, Serializer, Deserialize, Deserializer,
+                    de::{ self, Visitor }
+                };
+                use super::*;
+
+                serde!(PrivateKey);
+                serde!(PublicKey);
+                serde!(SignatureData);
+            }
+        }
+    }
+}
+
+sphincs!(shake128s, Shake128s, "SLH-DSA-SHAKE-128s"; 16, 7856);
+sphincs!(shake128f, Shake128f, "SLH-DSA-SHAKE-128f"; 16, 17088);
+sphincs!(shake192s, Shake192s, "SLH-DSA-SHAKE-192s"; 24, 16224);
+sphincs!(shake192f, Shake192f, "SLH-DSA-SHAKE-192f"; 24, 35664);
+sphincs!(shake256s, Shake256s, "SLH-DSA-SHAKE-256s"; 32, 29792);
+sphincs!(shake256f, Shake256f, "SLH-DSA-SHAKE-256f"; 32, 49856);
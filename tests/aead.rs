@@ -1,18 +1,27 @@
 extern crate rand;
 extern crate sarkara;
 
-use std::thread;
+use std::{ cmp, thread };
+use std::io::{ self, Read, Write, Seek, SeekFrom, Cursor };
 use std::sync::mpsc::channel;
 use rand::{ Rng, RngCore, FromEntropy, ChaChaRng };
-use sarkara::Error;
+use sarkara::{ ByteArray, Error };
 use sarkara::aead::{ AeadCipher, Online, Encryption, Decryption };
+use sarkara::aead::norx3241::Norx3241;
 use sarkara::aead::norx6441::Norx6441;
+use sarkara::aead::norx6444::Norx6444;
 use sarkara::aead::norx_mrs::NorxMRS;
+use sarkara::aead::xnorx::{ XNorx6441, hnorx, HNORX_NONCE_LENGTH };
+use sarkara::aead::io::{ EncryptWriter, DecryptReader };
+use sarkara::aead::committing::{ Committing, COMMITMENT_LENGTH };
+use sarkara::aead::nonce::NonceSequence;
+use sarkara::aead::rekey::{ Rekeying, Limits };
+use sarkara::aead::stream::{ SealStream, OpenStream, NONCE_SUFFIX_LENGTH };
 
 
 fn test_aead<AE: AeadCipher>() {
-    let mut key = vec![0u8; AE::KEY_LENGTH];
-    let mut nonce = vec![0u8; AE::NONCE_LENGTH];
+    let mut key = AE::Key::zero();
+    let mut nonce = AE::Nonce::zero();
     let mut rng = ChaChaRng::from_entropy();
 
     for i in 1..256 {
@@ -21,8 +30,8 @@ fn test_aead<AE: AeadCipher>() {
         let mut ct = vec![0u8; pt.len() + AE::TAG_LENGTH];
         let mut ot = vec![0u8; pt.len()];
 
-        rng.fill_bytes(&mut key);
-        rng.fill_bytes(&mut nonce);
+        rng.fill_bytes(key.as_mut());
+        rng.fill_bytes(nonce.as_mut());
         rng.fill_bytes(&mut aad);
         rng.fill_bytes(&mut pt);
 
@@ -32,31 +41,129 @@ fn test_aead<AE: AeadCipher>() {
 
         assert_eq!(pt, ot);
 
+        assert_eq!(cipher.seal_to_vec(&nonce, &aad, &pt).unwrap(), ct);
+        assert_eq!(cipher.open_to_vec(&nonce, &aad, &ct).unwrap(), pt);
+
         ct[i - 1] ^= 0x42;
         assert!(if let Err(Error::VerificationFailed) = cipher.open(&nonce, &aad, &ct, &mut ot) {
             true
         } else {
             false
         });
+        assert!(if let Err(Error::VerificationFailed) = cipher.open_to_vec(&nonce, &aad, &ct) {
+            true
+        } else {
+            false
+        });
+    }
+
+    let cipher = AE::new(&key);
+    assert!(if let Err(Error::Length) = cipher.open_to_vec(&nonce, &[], &vec![0; AE::TAG_LENGTH - 1]) {
+        true
+    } else {
+        false
+    });
+}
+
+fn test_aead_in_place<AE: AeadCipher>() {
+    let mut key = AE::Key::zero();
+    let mut nonce = AE::Nonce::zero();
+    let mut rng = ChaChaRng::from_entropy();
+
+    for i in 0..256 {
+        let mut aad = vec![0u8; rng.gen_range(0, 34)];
+        let mut pt = vec![0u8; i];
+        let mut ct = vec![0u8; pt.len() + AE::TAG_LENGTH];
+
+        rng.fill_bytes(key.as_mut());
+        rng.fill_bytes(nonce.as_mut());
+        rng.fill_bytes(&mut aad);
+        rng.fill_bytes(&mut pt);
+
+        let cipher = AE::new(&key);
+        cipher.seal(&nonce, &aad, &pt, &mut ct).unwrap();
+
+        let mut buf = pt.clone();
+        buf.resize(pt.len() + AE::TAG_LENGTH, 0);
+        cipher.seal_in_place(&nonce, &aad, &mut buf).unwrap();
+        assert_eq!(buf, ct);
+        cipher.open_in_place(&nonce, &aad, &mut buf).unwrap();
+        assert_eq!(&buf[..i], &pt[..]);
+
+        let mut buf = pt.clone();
+        let mut tag = cipher.seal_in_place_detached(&nonce, &aad, &mut buf).unwrap();
+        assert_eq!(&buf[..], &ct[..i]);
+        assert_eq!(tag.as_ref(), &ct[i..]);
+
+        tag.as_mut()[0] ^= 0x42;
+        assert!(if let Err(Error::VerificationFailed) = cipher.open_in_place_detached(&nonce, &aad, &mut buf, &tag) {
+            true
+        } else {
+            false
+        });
+        assert!(buf.iter().all(|&b| b == 0));
+
+        tag.as_mut()[0] ^= 0x42;
+        let mut buf = Vec::from(&ct[..i]);
+        cipher.open_in_place_detached(&nonce, &aad, &mut buf, &tag).unwrap();
+        assert_eq!(buf, pt);
+    }
+}
+
+/// The NORX reference KAT, `i` bytes of message and header for `i` in `0..256`.
+fn test_kat<AE: AeadCipher>(kat: &[u8]) {
+    let mut w = [0; 256];
+    let mut h = [0; 256];
+    let mut key = AE::Key::zero();
+    let mut nonce = AE::Nonce::zero();
+
+    for (i, b) in w.iter_mut().enumerate() {
+        *b = (i * 197 + 123) as u8;
+    }
+    for (i, b) in h.iter_mut().enumerate() {
+        *b = (i * 193 + 123) as u8;
+    }
+    for (i, b) in key.as_mut().iter_mut().enumerate() {
+        *b = (i * 191 + 123) as u8;
     }
+    for (i, b) in nonce.as_mut().iter_mut().enumerate() {
+        *b = (i * 181 + 123) as u8;
+    }
+
+    let cipher = AE::new(&key);
+    let mut kat = kat;
+    for i in 0..w.len() {
+        let ct = cipher.seal_to_vec(&nonce, &h[..i], &w[..i]).unwrap();
+        assert_eq!(ct, &kat[..ct.len()], "{} bytes", i);
+        kat = &kat[ct.len()..];
+    }
+    assert!(kat.is_empty());
 }
 
 fn test_onlineae<AE>()
     where
         for<'a> AE: AeadCipher + Online<'a>
 {
-    let mut key = vec![0u8; AE::KEY_LENGTH];
-    let mut nonce = vec![0u8; AE::NONCE_LENGTH];
+    let mut key = AE::Key::zero();
+    let mut nonce = AE::Nonce::zero();
     let mut rng = ChaChaRng::from_entropy();
 
     for i in 1..256 {
         let mut aad = vec![0u8; rng.gen_range(0, 34)];
         let mut pt = vec![0u8; i];
-        rng.fill_bytes(&mut key);
-        rng.fill_bytes(&mut nonce);
+        rng.fill_bytes(key.as_mut());
+        rng.fill_bytes(nonce.as_mut());
         rng.fill_bytes(&mut aad);
         rng.fill_bytes(&mut pt);
 
+        let cipher = AE::new(&key);
+        let mut process = cipher.encrypt(&nonce, &aad);
+        assert!(if let Err(Error::Length) = process.process(&[0; 1024], &mut [0; 512]) {
+            true
+        } else {
+            false
+        });
+
         let (send, recv) = channel();
 
         let key2 = key.clone();
@@ -66,17 +173,24 @@ fn test_onlineae<AE>()
         let a = thread::spawn(move || {
             let cipher = AE::new(&key2);
             let mut process = cipher.encrypt(&nonce2, &aad2);
+            let mut rng = ChaChaRng::from_entropy();
 
-            let mut ct = vec![0u8; pt2.len() + AE::TAG_LENGTH];
-            let mut buf = Vec::new();
+            let mut input = &pt2[..];
+            let mut pending = 0;
 
-            let ct1_len = process.process(&pt2, &mut ct).len();
-            buf.extend_from_slice(&pt2[ct1_len..]);
-            let (ct1, ct2) = ct.split_at_mut(ct1_len);
-            send.send(Vec::from(ct1)).unwrap();
+            while !input.is_empty() {
+                let n = rng.gen_range(0, input.len() + 1);
+                let mut ct = vec![0u8; pending + n];
+                let take = process.process(&input[..n], &mut ct).unwrap();
+                pending = pending + n - take;
+                ct.truncate(take);
+                send.send(ct).unwrap();
+                input = &input[n..];
+            }
 
-            process.finalize(&buf, ct2).unwrap();
-            send.send(Vec::from(ct2)).unwrap();
+            let mut ct = vec![0u8; pending + AE::TAG_LENGTH];
+            process.finalize(&[], &mut ct).unwrap();
+            send.send(ct).unwrap();
         });
 
         let key2 = key.clone();
@@ -87,15 +201,17 @@ fn test_onlineae<AE>()
             let mut process = cipher.decrypt(&nonce2, &aad2);
 
             let mut ot = vec![0u8; i];
-            let mut buf = Vec::new();
+            let mut tag = Vec::new();
+            let (mut read, mut written) = (0, 0);
 
-            let ct1 = recv.recv().unwrap();
-            let ot1_len = process.process(&ct1, &mut ot).len();
-            buf.extend_from_slice(&ct1[ot1_len..]);
+            for ct in recv {
+                let n = cmp::min(ct.len(), i - read);
+                written += process.process(&ct[..n], &mut ot[written..]).unwrap();
+                read += n;
+                tag.extend_from_slice(&ct[n..]);
+            }
 
-            let ct2 = recv.recv().unwrap();
-            buf.extend_from_slice(&ct2);
-            process.finalize(&buf, &mut ot[ot1_len..]).unwrap();
+            process.finalize(&tag, &mut ot[written..]).unwrap();
 
             assert_eq!(ot, pt);
         });
@@ -105,14 +221,386 @@ fn test_onlineae<AE>()
     }
 }
 
+fn test_io<AE>()
+    where
+        for<'a> AE: AeadCipher + Online<'a>
+{
+    let mut key = AE::Key::zero();
+    let mut nonce = AE::Nonce::zero();
+    let mut rng = ChaChaRng::from_entropy();
+
+    for i in 0..64 {
+        let mut aad = vec![0u8; rng.gen_range(0, 34)];
+        let mut pt = vec![0u8; i * 37];
+        rng.fill_bytes(key.as_mut());
+        rng.fill_bytes(nonce.as_mut());
+        rng.fill_bytes(&mut aad);
+        rng.fill_bytes(&mut pt);
+
+        let cipher = AE::new(&key);
+        let mut writer = EncryptWriter::new(&cipher, &nonce, &aad, Vec::new());
+        let mut input = &pt[..];
+        while !input.is_empty() {
+            let n = rng.gen_range(0, input.len() + 1);
+            writer.write_all(&input[..n]).unwrap();
+            input = &input[n..];
+        }
+        let mut ct = writer.finish().unwrap();
+        assert_eq!(ct.len(), pt.len() + AE::TAG_LENGTH);
+
+        let mut ot = Vec::new();
+        DecryptReader::new(&cipher, &nonce, &aad, &ct[..])
+            .read_to_end(&mut ot)
+            .unwrap();
+        assert_eq!(ot, pt);
+
+        let n = rng.gen_range(0, ct.len());
+        ct[n] ^= 0x42;
+        let mut reader = DecryptReader::new(&cipher, &nonce, &aad, &ct[..]);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // retrying must not look like a clean EOF
+        let err = reader.read(&mut [0; 64]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut reader = DecryptReader::new(&cipher, &nonce, &aad, &ct[..AE::TAG_LENGTH - 1]);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = reader.read(&mut [0; 64]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+fn test_stream<AE: AeadCipher>() {
+    let mut key = AE::Key::zero();
+    let mut prefix = vec![0u8; AE::NONCE_LENGTH - NONCE_SUFFIX_LENGTH];
+    let mut rng = ChaChaRng::from_entropy();
+
+    for _ in 0..16 {
+        let mut aad = vec![0u8; rng.gen_range(0, 34)];
+        let segments = (0..rng.gen_range(1, 8))
+            .map(|_| {
+                let mut pt = vec![0u8; rng.gen_range(0, 300)];
+                rng.fill_bytes(&mut pt);
+                pt
+            })
+            .collect::<Vec<_>>();
+
+        rng.fill_bytes(key.as_mut());
+        rng.fill_bytes(&mut prefix);
+        rng.fill_bytes(&mut aad);
+
+        let (last, init) = segments.split_last().unwrap();
+        let mut cts = Vec::new();
+        let mut sealing = SealStream::new(AE::new(&key), &prefix).unwrap();
+        for pt in init {
+            let mut ct = vec![0u8; pt.len() + AE::TAG_LENGTH];
+            sealing.seal(&aad, pt, &mut ct).unwrap();
+            cts.push(ct);
+        }
+        let mut ct = vec![0u8; last.len() + AE::TAG_LENGTH];
+        sealing.seal_last(&aad, last, &mut ct).unwrap();
+        cts.push(ct);
+
+        let mut opening = OpenStream::new(AE::new(&key), &prefix).unwrap();
+        let (last_ct, init_ct) = cts.split_last().unwrap();
+        for (ct, pt) in init_ct.iter().zip(init) {
+            let mut ot = vec![0u8; pt.len()];
+            opening.open(&aad, ct, &mut ot).unwrap();
+            assert_eq!(&ot, pt);
+        }
+        let mut ot = vec![0u8; last.len()];
+        opening.open_last(&aad, last_ct, &mut ot).unwrap();
+        assert_eq!(&ot, last);
+
+        // truncation
+        if let Some((ct, pt)) = init_ct.last().zip(init.last()) {
+            let mut opening = OpenStream::new(AE::new(&key), &prefix).unwrap();
+            let mut ot = vec![0u8; pt.len()];
+            for ct in &init_ct[..init_ct.len() - 1] {
+                let mut ot = vec![0u8; ct.len() - AE::TAG_LENGTH];
+                opening.open(&aad, ct, &mut ot).unwrap();
+            }
+            assert!(if let Err(Error::VerificationFailed) = opening.open_last(&aad, ct, &mut ot) {
+                true
+            } else {
+                false
+            });
+        }
+
+        // reordering
+        if cts.len() > 1 {
+            let mut opening = OpenStream::new(AE::new(&key), &prefix).unwrap();
+            let mut ot = vec![0u8; cts[1].len() - AE::TAG_LENGTH];
+            assert!(if let Err(Error::VerificationFailed) = opening.open(&aad, &cts[1], &mut ot) {
+                true
+            } else {
+                false
+            });
+        }
+    }
+}
+
 
 #[test]
 fn test_norx6441() {
     test_aead::<Norx6441>();
+    test_aead_in_place::<Norx6441>();
     test_onlineae::<Norx6441>();
+    test_io::<Norx6441>();
+    test_stream::<Norx6441>();
+}
+
+#[test]
+fn test_norx6444() {
+    test_kat::<Norx6444>(&include!("kat_config_6444.txt"));
+    test_aead::<Norx6444>();
+    test_aead_in_place::<Norx6444>();
+    test_onlineae::<Norx6444>();
+    test_io::<Norx6444>();
+    test_stream::<Norx6444>();
+}
+
+#[test]
+fn test_norx3241() {
+    test_kat::<Norx3241>(&include!("kat_config_3241.txt"));
+    test_aead::<Norx3241>();
+    test_aead_in_place::<Norx3241>();
+    test_onlineae::<Norx3241>();
+    test_io::<Norx3241>();
+    test_stream::<Norx3241>();
 }
 
 #[test]
 fn test_norx_mrs() {
     test_aead::<NorxMRS>();
+    test_aead_in_place::<NorxMRS>();
+    test_stream::<NorxMRS>();
+}
+
+#[test]
+fn test_norx_mrs_stream() {
+    let mut key = [0; NorxMRS::KEY_LENGTH];
+    let mut nonce = [0; NorxMRS::NONCE_LENGTH];
+    let mut rng = ChaChaRng::from_entropy();
+
+    for &len in &[0, 1, 95, 96, 97, 127, 128, 129, 383, 384, 385, 1000, 4096] {
+        let mut aad = vec![0u8; rng.gen_range(0, 300)];
+        let mut pt = vec![0u8; len];
+        rng.fill_bytes(&mut key);
+        rng.fill_bytes(&mut nonce);
+        rng.fill_bytes(&mut aad);
+        rng.fill_bytes(&mut pt);
+
+        let cipher = NorxMRS::new(&key);
+        let ct = cipher.seal_to_vec(&nonce, &aad, &pt).unwrap();
+
+        // starts at the current position
+        let mut input = Cursor::new([&b"prefix"[..], &pt].concat());
+        input.seek(SeekFrom::Start(6)).unwrap();
+        let mut output = Vec::new();
+        cipher.seal_stream(&nonce, &aad, &mut input, &mut output).unwrap();
+        assert_eq!(output, ct);
+
+        let mut input = Cursor::new([&b"prefix"[..], &ct].concat());
+        input.seek(SeekFrom::Start(6)).unwrap();
+        let mut output = Vec::new();
+        cipher.open_stream(&nonce, &aad, &mut input, &mut output).unwrap();
+        assert_eq!(output, pt);
+
+        let mut ct = ct;
+        let pos = rng.gen_range(0, ct.len());
+        ct[pos] ^= 0x42;
+        let mut output = Vec::new();
+        let err = cipher.open_stream(&nonce, &aad, Cursor::new(&ct), &mut output).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(output.is_empty());
+    }
+
+    let cipher = NorxMRS::new(&key);
+    let err = cipher.open_stream(&nonce, &[], Cursor::new(&[0; 31][..]), io::sink()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    // the source changes after the first pass
+    let ct = cipher.seal_to_vec(&nonce, &[], &[0x42; 1000]).unwrap();
+    let mut ct2 = ct.clone();
+    ct2[0] ^= 0x42;
+    let input = ChangingInput { inner: Cursor::new(ct), next: Some(ct2), rewinds: 0 };
+    let err = cipher.open_stream(&nonce, &[], input, io::sink()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+/// Replaced by `next` on the second rewind, the start of the second pass.
+struct ChangingInput {
+    inner: Cursor<Vec<u8>>,
+    next: Option<Vec<u8>>,
+    rewinds: usize
+}
+
+impl Read for ChangingInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for ChangingInput {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        if let SeekFrom::Start(0) = pos {
+            self.rewinds += 1;
+            if self.rewinds == 2 {
+                if let Some(next) = self.next.take() {
+                    self.inner = Cursor::new(next);
+                }
+            }
+        }
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn test_xnorx6441() {
+    test_aead::<XNorx6441>();
+    test_aead_in_place::<XNorx6441>();
+    test_stream::<XNorx6441>();
+}
+
+#[test]
+fn test_committing() {
+    test_aead::<Committing<Norx6441>>();
+    test_aead_in_place::<Committing<Norx6441>>();
+    test_stream::<Committing<Norx6441>>();
+
+    let mut rng = ChaChaRng::from_entropy();
+    let mut key = [0; 32];
+    let mut nonce = [0; Norx6441::NONCE_LENGTH];
+    let mut pt = [0; 64];
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut nonce);
+    rng.fill_bytes(&mut pt);
+
+    let cipher = Committing::<Norx6441>::new(&key);
+    let mut ct = cipher.seal_to_vec(&nonce, &[], &pt).unwrap();
+
+    // the commitment depends on the nonce
+    let mut nonce2 = nonce;
+    nonce2[0] ^= 1;
+    let ct2 = cipher.seal_to_vec(&nonce2, &[], &pt).unwrap();
+    assert_ne!(ct[ct.len() - COMMITMENT_LENGTH..], ct2[ct2.len() - COMMITMENT_LENGTH..]);
+    assert!(cipher.open_to_vec(&nonce, &[], &ct2).is_err());
+
+    key[0] ^= 1;
+    let cipher2 = Committing::<Norx6441>::new(&key);
+    assert!(if let Err(Error::VerificationFailed) = cipher2.open_to_vec(&nonce, &[], &ct) {
+        true
+    } else {
+        false
+    });
+
+    let len = ct.len();
+    ct[len - 1] ^= 1;
+    assert!(if let Err(Error::VerificationFailed) = cipher.open_to_vec(&nonce, &[], &ct) {
+        true
+    } else {
+        false
+    });
+}
+
+#[test]
+fn test_rekeying() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut key = [0; 32];
+    let nonce = [0; Norx6441::NONCE_LENGTH];
+    let mut pt = [0; 40];
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut pt);
+
+    let limits = Limits { messages: 3, bytes: 100 };
+    let mut sealing = Rekeying::<Norx6441>::new(&key, limits);
+    let mut opening = Rekeying::<Norx6441>::new(&key, limits);
+
+    let mut cts = Vec::new();
+    for i in 0..8 {
+        let ct = sealing.seal_to_vec(&nonce, &[], &pt[..i * 5]).unwrap();
+        assert_eq!(opening.open_to_vec(&nonce, &[], &ct).unwrap(), &pt[..i * 5]);
+        assert_eq!(sealing.epoch(), opening.epoch());
+        cts.push(ct);
+    }
+    assert_eq!(sealing.epoch(), 2);
+
+    // same nonce and plaintext, different epoch
+    let ct = sealing.seal_to_vec(&nonce, &[], &pt[..5]).unwrap();
+    assert_ne!(ct, cts[1]);
+
+    // the ratchet is not the XNorx6441 subkey for a zero nonce prefix
+    let mut subkey = [0; 32];
+    hnorx(&key, &[0; HNORX_NONCE_LENGTH], &mut subkey);
+    let ct = Norx6441::new(&subkey).seal_to_vec(&nonce, &[], &pt[..15]).unwrap();
+    assert_ne!(ct, cts[3]);
+
+    let mut opening = Rekeying::<Norx6441>::new(&key, limits);
+    for ct in &cts[..2] {
+        opening.open_to_vec(&nonce, &[], ct).unwrap();
+    }
+    // skipped message crosses the limit
+    assert!(if let Err(Error::VerificationFailed) = opening.open_to_vec(&nonce, &[], &cts[3]) {
+        true
+    } else {
+        false
+    });
+    assert_eq!(opening.epoch(), 0);
+}
+
+#[test]
+fn test_hnorx() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut key = [0; 32];
+    let mut nonce = [0; 32];
+    let mut subkey = [0; 32];
+    let mut subkey2 = [0; 32];
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut nonce);
+
+    hnorx(&key, &nonce, &mut subkey);
+    hnorx(&key, &nonce, &mut subkey2);
+    assert_eq!(subkey, subkey2);
+
+    nonce[0] ^= 1;
+    hnorx(&key, &nonce, &mut subkey2);
+    assert_ne!(subkey, subkey2);
+
+    nonce[0] ^= 1;
+    key[0] ^= 1;
+    hnorx(&key, &nonce, &mut subkey2);
+    assert_ne!(subkey, subkey2);
+}
+
+#[test]
+fn test_nonce_sequence() {
+    let prefix = vec![0x42; Norx6441::NONCE_LENGTH - 1];
+    let mut nonces = NonceSequence::<Norx6441>::with_prefix(&prefix).unwrap();
+
+    for i in 0..256 {
+        let nonce = nonces.advance().unwrap();
+        assert_eq!(&nonce[..prefix.len()], &prefix[..]);
+        assert_eq!(nonce[prefix.len()], i as u8);
+    }
+
+    assert!(if let Err(Error::NonceExhausted) = nonces.advance() {
+        true
+    } else {
+        false
+    });
+
+    let mut nonces = NonceSequence::<Norx6441>::new();
+    assert_eq!(nonces.advance().unwrap(), &[0; Norx6441::NONCE_LENGTH][..]);
+    let mut nonce = [0; Norx6441::NONCE_LENGTH];
+    nonce[Norx6441::NONCE_LENGTH - 1] = 1;
+    assert_eq!(nonces.advance().unwrap(), &nonce[..]);
+
+    assert!(if let Err(Error::Length) = NonceSequence::<Norx6441>::with_prefix(&[0; Norx6441::NONCE_LENGTH]) {
+        true
+    } else {
+        false
+    });
 }
@@ -1,34 +1,36 @@
 extern crate rand;
 extern crate sarkara;
 
-use rand::{ FromEntropy, ChaChaRng };
-use sarkara::{ Packing, Error };
-use sarkara::kex::{ KeyExchange, CheckedExchange };
+use rand::{ RngCore, FromEntropy, ChaChaRng };
+use sarkara::{ Packing, ByteArray, Error };
+use sarkara::kex::{ KeyExchange, CheckedExchange, SeedableExchange, SEED_LENGTH };
 use sarkara::kex::kyber::Kyber;
+use sarkara::kex::mlkem;
+use sarkara::sign::prehash::prehash;
 
 
 fn test_kex<KEX: KeyExchange>() {
-    let (mut a, mut b) = (vec![0u8; KEX::SHARED_LENGTH], vec![0u8; KEX::SHARED_LENGTH]);
+    let (mut a, mut b) = (KEX::SharedKey::zero(), KEX::SharedKey::zero());
     let mut rng = ChaChaRng::from_entropy();
 
     let (ska, pka) = KEX::keypair(&mut rng);
     let msg = KEX::exchange_to(&mut rng, &mut b, &pka);
     KEX::exchange_from(&mut a, &ska, &msg);
 
-    assert_eq!(a, b);
+    assert_eq!(a.as_ref(), b.as_ref());
 }
 
 fn test_checkedkex<KEX: CheckedExchange>() {
-    let (mut a, mut b) = (vec![0u8; KEX::SHARED_LENGTH], vec![0u8; KEX::SHARED_LENGTH]);
+    let (mut a, mut b) = (KEX::SharedKey::zero(), KEX::SharedKey::zero());
     let mut rng = ChaChaRng::from_entropy();
 
     let (ska, pka) = KEX::keypair(&mut rng);
     let msg = KEX::exchange_to(&mut rng, &mut b, &pka);
 
-    let mut fake_msg = vec![0u8; KEX::Message::BYTES_LENGTH];
-    msg.read_bytes(|msg| fake_msg.copy_from_slice(msg));
-    fake_msg[0] ^= 0x42;
-    fake_msg[KEX::Message::BYTES_LENGTH - 1] ^= 0x43;
+    let mut fake_msg = <KEX::Message as Packing>::Bytes::zero();
+    msg.read_bytes(|msg| fake_msg.as_mut().copy_from_slice(msg));
+    fake_msg.as_mut()[0] ^= 0x42;
+    fake_msg.as_mut()[KEX::Message::BYTES_LENGTH - 1] ^= 0x43;
     let fake_msg = KEX::Message::from_bytes(&fake_msg);
 
     let r = <KEX as CheckedExchange>::exchange_from(&mut a, &ska, &fake_msg);
@@ -40,9 +42,85 @@ fn test_checkedkex<KEX: CheckedExchange>() {
     });
 }
 
+/// Implicit rejection, a tampered message gives an unrelated shared key.
+fn test_rejection<KEX: CheckedExchange>() {
+    let (mut a, mut b) = (KEX::SharedKey::zero(), KEX::SharedKey::zero());
+    let mut rng = ChaChaRng::from_entropy();
+
+    let (ska, pka) = KEX::keypair(&mut rng);
+    let msg = KEX::exchange_to(&mut rng, &mut b, &pka);
+
+    let mut fake_msg = <KEX::Message as Packing>::Bytes::zero();
+    msg.read_bytes(|msg| fake_msg.as_mut().copy_from_slice(msg));
+    fake_msg.as_mut()[KEX::Message::BYTES_LENGTH - 1] ^= 0x43;
+    let fake_msg = KEX::Message::from_bytes(&fake_msg);
+
+    assert!(<KEX as CheckedExchange>::exchange_from(&mut a, &ska, &fake_msg).is_ok());
+    assert_ne!(a.as_ref(), b.as_ref());
+    assert_ne!(a.as_ref(), KEX::SharedKey::zero().as_ref());
+}
+
+fn test_seed<KEX: SeedableExchange>() {
+    let (mut a, mut b) = (KEX::SharedKey::zero(), KEX::SharedKey::zero());
+    let mut rng = ChaChaRng::from_entropy();
+    let mut seed = [0; SEED_LENGTH];
+    rng.fill_bytes(&mut seed);
+
+    let (sk, pk) = KEX::keypair_from_seed(&seed);
+    let (sk2, pk2) = KEX::keypair_from_seed(&seed);
+    assert!(sk.read_bytes(|sk| sk2.read_bytes(|sk2| sk == sk2)));
+    assert!(pk.read_bytes(|pk| pk2.read_bytes(|pk2| pk == pk2)));
+
+    let msg = KEX::exchange_to(&mut rng, &mut b, &pk);
+    KEX::exchange_from(&mut a, &sk2, &msg);
+    assert_eq!(a.as_ref(), b.as_ref());
+
+    seed[0] ^= 0x42;
+    let (_, pk2) = KEX::keypair_from_seed(&seed);
+    assert!(pk.read_bytes(|pk| pk2.read_bytes(|pk2| pk != pk2)));
+}
+
+/// `seed` is `0x00..0x1f`, the digests are the first 32 bytes of SHAKE256 of the keys.
+fn test_seed_kat<KEX: SeedableExchange>(pk_digest: &str, sk_digest: &str) {
+    let mut seed = [0; SEED_LENGTH];
+    for (i, b) in seed.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+
+    let (sk, pk) = KEX::keypair_from_seed(&seed);
+    assert_eq!(pk.read_bytes(digest), pk_digest);
+    assert_eq!(sk.read_bytes(digest), sk_digest);
+}
+
+fn digest(bytes: &[u8]) -> String {
+    prehash(bytes)[..32].iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 
 #[test]
 fn test_kyber() {
     test_kex::<Kyber>();
     test_checkedkex::<Kyber>();
+    test_seed::<Kyber>();
+    test_seed_kat::<Kyber>(
+        "191d57b2dac4e8ca9a16e0b80d00f156c5508ae2cf3acbbb70e95e3d0933425c",
+        "24b719e1d17951be8aa7b9b61de18705e0541825d1a365c2499b87ed2c421c21"
+    );
+}
+
+#[test]
+fn test_mlkem() {
+    test_kex::<mlkem::mlkem512::MlKem>();
+    test_rejection::<mlkem::mlkem512::MlKem>();
+    test_kex::<mlkem::mlkem768::MlKem>();
+    test_rejection::<mlkem::mlkem768::MlKem>();
+    test_seed::<mlkem::mlkem768::MlKem>();
+    test_seed_kat::<mlkem::mlkem768::MlKem>(
+        "232f4de0e4d7d6e000823763f6be2e6df44fddb37569208914d616ff0e592cb4",
+        "8e2d1547739cf613f1725568c0e4c4fdfee1c3d6a8eb2f8c17652fea1ab4f3f7"
+    );
+    test_kex::<mlkem::mlkem1024::MlKem>();
+    test_rejection::<mlkem::mlkem1024::MlKem>();
 }
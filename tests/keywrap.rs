@@ -0,0 +1,56 @@
+extern crate rand;
+extern crate sarkara;
+
+use rand::{ RngCore, FromEntropy, ChaChaRng };
+use sarkara::{ Packing, Error };
+use sarkara::kex::KeyExchange;
+use sarkara::kex::kyber::Kyber;
+use sarkara::sign::Signature;
+use sarkara::sign::dilithium::Dilithium;
+use sarkara::aead::AeadCipher;
+use sarkara::aead::norx_mrs::NorxMRS;
+use sarkara::keywrap::KeyWrap;
+
+
+fn test_keywrap<P: Packing + Eq, Q: Packing>(value: &P) {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut kek = [0; NorxMRS::KEY_LENGTH];
+    rng.fill_bytes(&mut kek);
+
+    let kw = KeyWrap::new(&kek);
+    let mut wrapped = kw.wrap(b"id:1", value);
+    assert_eq!(wrapped.len(), P::BYTES_LENGTH + KeyWrap::OVERHEAD);
+    assert_eq!(wrapped, kw.wrap(b"id:1", value));
+    assert!(kw.unwrap::<P>(b"id:1", &wrapped).unwrap() == *value);
+
+    assert!(if let Err(Error::VerificationFailed) = kw.unwrap::<P>(b"id:2", &wrapped) {
+        true
+    } else {
+        false
+    });
+    assert!(if let Err(Error::Length) = kw.unwrap::<Q>(b"id:1", &wrapped) {
+        true
+    } else {
+        false
+    });
+
+    wrapped[0] ^= 0x42;
+    assert!(if let Err(Error::VerificationFailed) = kw.unwrap::<P>(b"id:1", &wrapped) {
+        true
+    } else {
+        false
+    });
+}
+
+
+#[test]
+fn test_keywrap_kyber() {
+    let (sk, _) = Kyber::keypair(ChaChaRng::from_entropy());
+    test_keywrap::<_, <Kyber as KeyExchange>::PublicKey>(&sk);
+}
+
+#[test]
+fn test_keywrap_dilithium() {
+    let (sk, _) = Dilithium::keypair(ChaChaRng::from_entropy());
+    test_keywrap::<_, <Dilithium as Signature>::PublicKey>(&sk);
+}
@@ -2,27 +2,31 @@ extern crate rand;
 extern crate sarkara;
 
 use rand::{ Rng, RngCore, FromEntropy, ChaChaRng };
+use sarkara::ByteArray;
 use sarkara::aead::AeadCipher;
 use sarkara::kex::KeyExchange;
 use sarkara::sealedbox::SealedBox;
+use sarkara::aead::rekey::Limits;
 
 use sarkara::kex::kyber::Kyber;
+use sarkara::kex::mlkem::mlkem768::MlKem;
 use sarkara::aead::norx6441::Norx6441;
+use sarkara::aead::committing::Committing;
 
-fn test_sealedbox<KEX: KeyExchange, AE: AeadCipher>() {
+fn test_sealedbox<KEX: KeyExchange, AE: AeadCipher<Key = KEX::SharedKey>>() {
     let mut rng = ChaChaRng::from_entropy();
     let (bob_priv, bob_pub) = KEX::keypair(&mut rng);
 
     let (alice_msg, alice_enc) = SealedBox::<KEX, AE>::send(&mut rng, &bob_pub);
     let bob_dec = SealedBox::<KEX, AE>::recv(&bob_priv, &alice_msg);
 
-    let mut nonce = vec![0u8; AE::NONCE_LENGTH];
+    let mut nonce = AE::Nonce::zero();
     let mut aad = vec![0u8; rng.gen_range(0, 34)];
     let mut pt = vec![0u8; 32];
     let mut ct = vec![0u8; pt.len() + AE::TAG_LENGTH];
     let mut ot = vec![0u8; pt.len()];
 
-    rng.fill_bytes(&mut nonce);
+    rng.fill_bytes(nonce.as_mut());
     rng.fill_bytes(&mut aad);
     rng.fill_bytes(&mut pt);
 
@@ -30,6 +34,33 @@ fn test_sealedbox<KEX: KeyExchange, AE: AeadCipher>() {
     bob_dec.open(&nonce, &aad, &ct, &mut ot).unwrap();
 
     assert_eq!(pt, ot);
+
+    let ct = alice_enc.seal_to_vec(&nonce, &aad, &pt).unwrap();
+    assert_eq!(ct.len(), pt.len() + AE::TAG_LENGTH);
+    assert_eq!(bob_dec.open_to_vec(&nonce, &aad, &ct).unwrap(), pt);
+}
+
+fn test_sealedbox_rekeying<KEX: KeyExchange<SharedKey = [u8; 32]>, AE: AeadCipher<Key = [u8; 32]>>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let (bob_priv, bob_pub) = KEX::keypair(&mut rng);
+    let limits = Limits { messages: 4, bytes: 1024 };
+
+    let (alice_msg, mut alice_enc) = SealedBox::<KEX, AE>::send_rekeying(&mut rng, &bob_pub, limits);
+    let mut bob_dec = SealedBox::<KEX, AE>::recv_rekeying(&bob_priv, &alice_msg, limits);
+
+    let mut nonce = AE::Nonce::zero();
+
+    for _ in 0..16 {
+        let mut pt = vec![0u8; rng.gen_range(0, 300)];
+        rng.fill_bytes(nonce.as_mut());
+        rng.fill_bytes(&mut pt);
+
+        let ct = alice_enc.seal_to_vec(&nonce, &[], &pt).unwrap();
+        assert_eq!(bob_dec.open_to_vec(&nonce, &[], &ct).unwrap(), pt);
+        assert_eq!(alice_enc.epoch(), bob_dec.epoch());
+    }
+
+    assert!(alice_enc.epoch() >= 4);
 }
 
 
@@ -37,3 +68,19 @@ fn test_sealedbox<KEX: KeyExchange, AE: AeadCipher>() {
 fn test_kyber_norx() {
     test_sealedbox::<Kyber, Norx6441>();
 }
+
+#[test]
+fn test_kyber_committing_norx() {
+    test_sealedbox::<Kyber, Committing<Norx6441>>();
+}
+
+#[test]
+fn test_kyber_norx_rekeying() {
+    test_sealedbox_rekeying::<Kyber, Norx6441>();
+}
+
+#[test]
+fn test_mlkem_norx() {
+    test_sealedbox::<MlKem, Norx6441>();
+    test_sealedbox_rekeying::<MlKem, Norx6441>();
+}
@@ -1,9 +1,14 @@
 extern crate rand;
 extern crate sarkara;
 
+use std::io;
 use rand::{ Rng, RngCore, FromEntropy, ChaChaRng };
-use sarkara::sign::{ Signature, DeterministicSignature };
+use sarkara::{ Packing, ByteArray, Error };
+use sarkara::sign::{ Signature, DeterministicSignature, SeedableSignature, StatefulSignature, StateStore, Composite, SEED_LENGTH };
+use sarkara::sign::prehash::prehash;
 use sarkara::sign::dilithium::Dilithium;
+use sarkara::sign::{ sphincs, falcon, mldsa };
+use sarkara::sign::lms::{ self, Lms };
 
 
 fn test_sign<SS: Signature>() {
@@ -32,9 +37,318 @@ fn test_dsign<SS: DeterministicSignature>() {
     assert!(SS::verify(&pk, &sig, &data).is_err());
 }
 
+fn test_prehash<SS: Signature>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut data = vec![0; rng.gen_range(1, 100 * 1024)];
+    rng.fill_bytes(&mut data);
+
+    let (sk, pk) = SS::keypair(&mut rng);
+    let sig = SS::signature_reader(&mut rng, &sk, b"", &data[..]).unwrap();
+    assert!(SS::verify_prehashed(&pk, &sig, b"", &prehash(&data)).is_ok());
+    assert!(SS::verify_reader(&pk, &sig, b"", &data[..]).is_ok());
+    assert!(SS::verify(&pk, &sig, &data).is_err());
+    assert!(SS::verify_reader(&pk, &sig, b"release", &data[..]).is_err());
+
+    let sig = SS::signature_prehashed(&mut rng, &sk, b"release", &prehash(&data)).unwrap();
+    assert!(SS::verify_reader(&pk, &sig, b"release", &data[..]).is_ok());
+
+    data[0] ^= 0x42;
+    assert!(SS::verify_prehashed(&pk, &sig, b"release", &prehash(&data)).is_err());
+    assert_eq!(SS::verify_reader(&pk, &sig, b"release", &data[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+fn test_context<SS: DeterministicSignature>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut data = vec![0; rng.gen_range(1, 2049)];
+    rng.fill_bytes(&mut data);
+
+    let (sk, pk) = SS::keypair(&mut rng);
+    let sig = <SS as Signature>::signature_with_context(&mut rng, &sk, b"object-a", &data).unwrap();
+    assert!(SS::verify_with_context(&pk, &sig, b"object-a", &data).is_ok());
+    assert!(SS::verify_with_context(&pk, &sig, b"object-b", &data).is_err());
+    assert!(SS::verify_with_context(&pk, &sig, b"", &data).is_err());
+    assert!(SS::verify(&pk, &sig, &data).is_err());
+
+    let sig = <SS as DeterministicSignature>::signature_with_context(&sk, b"", &data).unwrap();
+    assert!(SS::verify_with_context(&pk, &sig, b"", &data).is_ok());
+
+    let ctx = [0; 256];
+    assert!(if let Err(Error::Length) = <SS as DeterministicSignature>::signature_with_context(&sk, &ctx, &data) {
+        true
+    } else {
+        false
+    });
+    assert!(if let Err(Error::Length) = SS::verify_with_context(&pk, &sig, &ctx, &data) {
+        true
+    } else {
+        false
+    });
+}
+
+/// For schemes where `signature` is the empty context case.
+fn test_empty_context<SS: Signature>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut data = vec![0; rng.gen_range(1, 2049)];
+    rng.fill_bytes(&mut data);
+
+    let (sk, pk) = SS::keypair(&mut rng);
+    let sig = SS::signature(&mut rng, &sk, &data);
+    assert!(SS::verify_with_context(&pk, &sig, b"", &data).is_ok());
+    assert!(SS::verify_with_context(&pk, &sig, b"ctx", &data).is_err());
+
+    let sig = SS::signature_with_context(&mut rng, &sk, b"", &data).unwrap();
+    assert!(SS::verify(&pk, &sig, &data).is_ok());
+}
+
+fn test_verify_batch<SS: Signature>()
+    where
+        SS::PublicKey: Sync,
+        SS::Signature: Sync
+{
+    let mut rng = ChaChaRng::from_entropy();
+
+    let items = (0..37)
+        .map(|_| {
+            let mut data = vec![0; rng.gen_range(1, 2049)];
+            rng.fill_bytes(&mut data);
+            let (sk, pk) = SS::keypair(&mut rng);
+            let sig = SS::signature(&mut rng, &sk, &data);
+            (pk, sig, data)
+        })
+        .collect::<Vec<_>>();
+
+    let mut batch = items.iter()
+        .map(|(pk, sig, data)| (pk, sig, &data[..]))
+        .collect::<Vec<_>>();
+    assert!(SS::verify_batch(&batch).iter().all(Result::is_ok));
+
+    // wrong key
+    batch[3].0 = &items[4].0;
+    let results = SS::verify_batch(&batch);
+    assert_eq!(results.len(), items.len());
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.is_ok(), i != 3);
+    }
+
+    assert!(SS::verify_batch(&[]).is_empty());
+}
+
+fn test_hedged<SS: DeterministicSignature>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut data = vec![0; rng.gen_range(1, 2049)];
+    rng.fill_bytes(&mut data);
+
+    let (sk, pk) = SS::keypair(&mut rng);
+    let sig = <SS as DeterministicSignature>::signature(&sk, &data);
+    let sig2 = <SS as DeterministicSignature>::signature(&sk, &data);
+    assert!(sig.read_bytes(|sig| sig2.read_bytes(|sig2| sig == sig2)));
+
+    let sig = <SS as Signature>::signature(&mut rng, &sk, &data);
+    let sig2 = <SS as Signature>::signature(&mut rng, &sk, &data);
+    assert!(sig.read_bytes(|sig| sig2.read_bytes(|sig2| sig != sig2)));
+    assert!(SS::verify(&pk, &sig, &data).is_ok());
+    assert!(SS::verify(&pk, &sig2, &data).is_ok());
+}
+
+fn test_seed<SS: SeedableSignature>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut seed = [0; SEED_LENGTH];
+    rng.fill_bytes(&mut seed);
+
+    let (sk, pk) = SS::keypair_from_seed(&seed);
+    let (sk2, pk2) = SS::keypair_from_seed(&seed);
+    assert!(sk.read_bytes(|sk| sk2.read_bytes(|sk2| sk == sk2)));
+    assert!(pk.read_bytes(|pk| pk2.read_bytes(|pk2| pk == pk2)));
+
+    let sig = SS::signature(&mut rng, &sk2, b"seed");
+    assert!(SS::verify(&pk, &sig, b"seed").is_ok());
+
+    seed[0] ^= 0x42;
+    let (_, pk2) = SS::keypair_from_seed(&seed);
+    assert!(pk.read_bytes(|pk| pk2.read_bytes(|pk2| pk != pk2)));
+}
+
+/// `seed` is `0x00..0x1f`, the digests are the first 32 bytes of SHAKE256 of the keys.
+fn test_seed_kat<SS: SeedableSignature>(pk_digest: &str, sk_digest: &str) {
+    let mut seed = [0; SEED_LENGTH];
+    for (i, b) in seed.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+
+    let (sk, pk) = SS::keypair_from_seed(&seed);
+    assert_eq!(pk.read_bytes(digest), pk_digest);
+    assert_eq!(sk.read_bytes(digest), sk_digest);
+}
+
+fn digest(bytes: &[u8]) -> String {
+    prehash(bytes)[..32].iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+struct MemoryStore(Vec<u8>);
+
+impl StateStore for MemoryStore {
+    fn store(&mut self, state: &[u8]) -> io::Result<()> {
+        self.0.clear();
+        self.0.extend_from_slice(state);
+        Ok(())
+    }
+}
+
+struct BrokenStore;
+
+impl StateStore for BrokenStore {
+    fn store(&mut self, _: &[u8]) -> io::Result<()> {
+        Err(io::ErrorKind::Other.into())
+    }
+}
+
+fn test_stateful<SS: StatefulSignature>() {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut data = vec![0; rng.gen_range(1, 2049)];
+    rng.fill_bytes(&mut data);
+
+    let (mut sk, pk) = SS::keypair(&mut rng);
+    let remaining = SS::remaining(&sk);
+    let mut store = MemoryStore(Vec::new());
+
+    let sig = SS::signature(&mut store, &mut sk, &data).unwrap();
+    assert!(SS::verify(&pk, &sig, &data).is_ok());
+    assert_eq!(SS::remaining(&sk), remaining - 1);
+    assert!(sk.read_bytes(|bytes| bytes == &store.0[..]));
+
+    // the key is advanced before the store fails
+    assert!(SS::signature(&mut BrokenStore, &mut sk, &data).is_err());
+    assert_eq!(SS::remaining(&sk), remaining - 2);
+
+    let sig2 = SS::signature(&mut store, &mut sk, &data).unwrap();
+    assert!(SS::verify(&pk, &sig2, &data).is_ok());
+    assert!(sig.read_bytes(|sig| sig2.read_bytes(|sig2| sig != sig2)));
+
+    data[0] ^= 0x42;
+    assert!(SS::verify(&pk, &sig2, &data).is_err());
+}
+
+#[test]
+fn test_lms() {
+    test_stateful::<Lms>();
+
+    let mut rng = ChaChaRng::from_entropy();
+    let (sk, pk) = Lms::keypair(&mut rng);
+    let mut store = MemoryStore(Vec::new());
+
+    // skip to the last one-time key
+    let mut bytes = [0; lms::PRIVATEKEYBYTES];
+    sk.read_bytes(|sk| bytes.copy_from_slice(sk));
+    bytes[..4].copy_from_slice(&(lms::MAX_SIGNATURES - 1).to_be_bytes());
+    let mut sk = lms::PrivateKey::from_bytes(&bytes);
+    assert_eq!(Lms::remaining(&sk), 1);
+
+    let sig = Lms::signature(&mut store, &mut sk, b"last").unwrap();
+    assert!(Lms::verify(&pk, &sig, b"last").is_ok());
+    assert_eq!(Lms::remaining(&sk), 0);
+
+    let err = Lms::signature(&mut store, &mut sk, b"one more").err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert!(sk.read_bytes(|bytes| bytes == &store.0[..]));
+}
+
 
 #[test]
 fn test_dilithium() {
     test_sign::<Dilithium>();
     test_dsign::<Dilithium>();
+    test_prehash::<Dilithium>();
+    test_context::<Dilithium>();
+    test_verify_batch::<Dilithium>();
+    test_seed::<Dilithium>();
+    test_seed_kat::<Dilithium>(
+        "ef732d6d3cbc64eb7d26d356345a06a603f2f152fe7e0c024ab1618083e7e247",
+        "f2afc03c986ec6c594f9ae9c09af6be29b21bb8b8b55dd7d0be913864435091a"
+    );
+    test_hedged::<Dilithium>();
+}
+
+#[test]
+fn test_sphincs() {
+    test_sign::<sphincs::shake128f::Sphincs>();
+    test_dsign::<sphincs::shake128f::Sphincs>();
+    test_context::<sphincs::shake128f::Sphincs>();
+    test_empty_context::<sphincs::shake128f::Sphincs>();
+    test_prehash::<sphincs::shake128f::Sphincs>();
+    test_seed::<sphincs::shake128f::Sphincs>();
+    test_sign::<sphincs::shake192s::Sphincs>();
+    test_sign::<sphincs::shake256s::Sphincs>();
+    test_sign::<sphincs::shake256f::Sphincs>();
+}
+
+#[test]
+fn test_falcon() {
+    test_sign::<falcon::falcon512::Falcon>();
+    test_prehash::<falcon::falcon512::Falcon>();
+    test_verify_batch::<falcon::falcon512::Falcon>();
+    test_sign::<falcon::falcon1024::Falcon>();
+}
+
+#[test]
+fn test_mldsa() {
+    test_sign::<mldsa::mldsa44::MlDsa>();
+    test_dsign::<mldsa::mldsa44::MlDsa>();
+    test_prehash::<mldsa::mldsa44::MlDsa>();
+    test_context::<mldsa::mldsa44::MlDsa>();
+    test_hedged::<mldsa::mldsa44::MlDsa>();
+    test_empty_context::<mldsa::mldsa44::MlDsa>();
+    test_seed::<mldsa::mldsa44::MlDsa>();
+
+    test_sign::<mldsa::mldsa65::MlDsa>();
+    test_context::<mldsa::mldsa65::MlDsa>();
+    test_hedged::<mldsa::mldsa65::MlDsa>();
+    test_empty_context::<mldsa::mldsa65::MlDsa>();
+
+    test_sign::<mldsa::mldsa87::MlDsa>();
+    test_context::<mldsa::mldsa87::MlDsa>();
+    test_hedged::<mldsa::mldsa87::MlDsa>();
+    test_empty_context::<mldsa::mldsa87::MlDsa>();
+}
+
+#[test]
+fn test_composite() {
+    type DilithiumFalcon = Composite<Dilithium, falcon::falcon512::Falcon>;
+    type DilithiumMlDsa = Composite<Dilithium, mldsa::mldsa65::MlDsa>;
+
+    test_sign::<DilithiumFalcon>();
+    test_prehash::<DilithiumFalcon>();
+    test_verify_batch::<DilithiumFalcon>();
+    test_sign::<DilithiumMlDsa>();
+    test_dsign::<DilithiumMlDsa>();
+    test_context::<DilithiumMlDsa>();
+    test_seed::<DilithiumMlDsa>();
+
+    let mut rng = ChaChaRng::from_entropy();
+    let (sk, pk) = DilithiumMlDsa::keypair(&mut rng);
+    let (sk2, pk2) = DilithiumMlDsa::keypair(&mut rng);
+    let sig = <DilithiumMlDsa as Signature>::signature(&mut rng, &sk, b"data");
+    let sig2 = <DilithiumMlDsa as Signature>::signature(&mut rng, &sk2, b"data");
+    assert_eq!(
+        <DilithiumMlDsa as Signature>::Signature::BYTES_LENGTH,
+        <Dilithium as Signature>::Signature::BYTES_LENGTH + mldsa::mldsa65::BYTES
+    );
+
+    // roundtrip through the concatenated bytes
+    let mut bytes = vec![0; <DilithiumMlDsa as Signature>::PublicKey::BYTES_LENGTH];
+    pk.read_bytes(|buf| bytes.copy_from_slice(buf));
+    let mut buf = <<DilithiumMlDsa as Signature>::PublicKey as Packing>::Bytes::zero();
+    buf.as_mut().copy_from_slice(&bytes);
+    assert!(<DilithiumMlDsa as Signature>::PublicKey::from_bytes(&buf) == pk);
+
+    // a component signature is not a signature of `data` alone
+    assert!(Dilithium::verify(&pk.0, &sig.0, b"data").is_err());
+    assert!(mldsa::mldsa65::MlDsa::verify(&pk.1, &sig.1, b"data").is_err());
+    assert!(sk.2 == pk);
+
+    // either half alone is not enough
+    let mixed = sarkara::sign::composite::SignatureData::<Dilithium, mldsa::mldsa65::MlDsa>(sig.0, sig2.1);
+    assert!(DilithiumMlDsa::verify(&pk, &mixed, b"data").is_err());
+    assert!(DilithiumMlDsa::verify(&pk2, &mixed, b"data").is_err());
 }